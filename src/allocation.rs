@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+/// Maximum number of passes over all actuators before [allocate] settles for its current solution.
+const MAX_SWEEPS: usize = 64;
+/// Penalty on throttle, so redundant actuators aren't fired against each other.
+const THROTTLE_PENALTY: f32 = 1e-6;
+
+/// Something that can push a ship, such as a [Thruster](crate::thruster::Thruster) or a reaction wheel.
+/// Described by what it produces at full throttle, in the ship's local frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Actuator {
+    /// Force on the ship at full throttle, in newtons.
+    pub force: Vec3,
+    /// Torque around the ship's center of mass at full throttle, in newton-metres.
+    pub torque: Vec3,
+}
+
+impl Actuator {
+    /// An actuator pushing with `force` at `position` relative to the center of mass.
+    pub fn at(position: Vec3, force: Vec3) -> Self {
+        Self {
+            force,
+            torque: position.cross(force),
+        }
+    }
+
+    /// An actuator producing a pure torque, like a reaction wheel.
+    pub fn torque(torque: Vec3) -> Self {
+        Self {
+            force: Vec3::ZERO,
+            torque,
+        }
+    }
+}
+
+/// Result of [allocate]: a throttle between `0.0` and `1.0` for each actuator,
+/// and what the actuators produce together at those settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Allocation {
+    pub throttles: Vec<f32>,
+    pub force: Vec3,
+    pub torque: Vec3,
+}
+
+/// Finds throttle settings for `actuators` which together come as close as possible to the
+/// requested `force` and `torque`, both in the ship's local frame.
+///
+/// Solves the bounded least-squares problem `min |F(u) - force|² + w²|T(u) - torque|²` with every
+/// throttle `u` in `[0, 1]` by cyclic coordinate descent. Unlike scaling the whole request down until
+/// it fits, axes with headroom keep their full authority when another axis saturates.
+/// `torque_weight` (`w`) trades off missing the torque against missing the force.
+pub fn allocate(
+    actuators: &[Actuator],
+    force: Vec3,
+    torque: Vec3,
+    torque_weight: f32,
+) -> Allocation {
+    let w2 = torque_weight * torque_weight;
+    let mut throttles = vec![0.0; actuators.len()];
+    let mut force_residual = -force;
+    let mut torque_residual = -torque;
+
+    for _ in 0..MAX_SWEEPS {
+        let mut largest_change = 0.0f32;
+
+        for (actuator, throttle) in actuators.iter().zip(throttles.iter_mut()) {
+            let curvature = actuator.force.length_squared()
+                + w2 * actuator.torque.length_squared()
+                + THROTTLE_PENALTY;
+            let gradient = actuator.force.dot(force_residual)
+                + w2 * actuator.torque.dot(torque_residual)
+                + THROTTLE_PENALTY * *throttle;
+
+            let updated = (*throttle - gradient / curvature).clamp(0.0, 1.0);
+            let change = updated - *throttle;
+            if change != 0.0 {
+                force_residual += actuator.force * change;
+                torque_residual += actuator.torque * change;
+                *throttle = updated;
+                largest_change = largest_change.max(change.abs());
+            }
+        }
+
+        if largest_change < 1e-5 {
+            break;
+        }
+    }
+
+    Allocation {
+        throttles,
+        force: force + force_residual,
+        torque: torque + torque_residual,
+    }
+}
+
+/// Outcome of the latest thrust allocation for a ship, in the ship's local frame.
+/// Controllers can compare the requested and achieved values to see how far short the ship fell.
+#[derive(Debug, Clone, Copy, Default, Component, Reflect)]
+pub struct ThrustAllocation {
+    pub requested_force: Vec3,
+    pub requested_torque: Vec3,
+    pub achieved_force: Vec3,
+    pub achieved_torque: Vec3,
+}
+
+impl ThrustAllocation {
+    /// Part of the requested force the ship's actuators couldn't deliver.
+    pub fn force_shortfall(&self) -> Vec3 {
+        self.requested_force - self.achieved_force
+    }
+
+    /// Part of the requested torque the ship's actuators couldn't deliver.
+    pub fn torque_shortfall(&self) -> Vec3 {
+        self.requested_torque - self.achieved_torque
+    }
+}
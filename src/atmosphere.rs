@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    kinematics::{world_motion, FrameQuery, Motion},
+    local_system::CelestialBody,
+    physics::{Force, Mass, PhysicsSet, SimulationTime},
+    sleep::Sleeping,
+    thruster::thruster_force_system,
+};
+
+/// Exponential atmosphere around a [CelestialBody], given in the body's `*.system.ron` entry.
+/// The density at altitude `h` above the body's surface is `surface_density * e^(-h / scale_height)`,
+/// and zero beyond [Atmosphere::radius]. The air moves along with the body.
+#[derive(Debug, Clone, Copy, Deserialize, Component, Reflect)]
+pub struct Atmosphere {
+    /// Density at the surface of the body, in kg per cubic world unit.
+    pub surface_density: f32,
+    /// Altitude over which the density drops by a factor of `e`, in world units.
+    pub scale_height: f32,
+    /// Distance from the body's center where the atmosphere ends, in world units.
+    pub radius: f32,
+}
+
+impl Atmosphere {
+    /// Density at `distance` from the center of a body of radius `surface`.
+    pub fn density(&self, distance: f32, surface: f32) -> f32 {
+        if distance > self.radius {
+            return 0.0;
+        }
+
+        let altitude = (distance - surface).max(0.0);
+        self.surface_density * (-altitude / self.scale_height).exp()
+    }
+}
+
+/// Density and world-space airspeed of the air an entity moving with `motion` flies through, or `None`
+/// in vacuum. `atmospheres` holds the world-space [Motion] of each [Atmosphere]'s body along with the
+/// body's radius. Where atmospheres overlap, the densest one wins.
+pub fn air_at(motion: &Motion, atmospheres: &[(Motion, Atmosphere, f32)]) -> Option<(f32, Vec3)> {
+    atmospheres
+        .iter()
+        .map(|(frame, atmosphere, surface)| {
+            let relative = motion.relative_to(frame);
+            let density = atmosphere.density(relative.transform.translation.length(), *surface);
+            (density, frame.transform.rotation * relative.velocity)
+        })
+        .filter(|(density, _)| *density > 0.0)
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+}
+
+/// Quadratic drag force on an entity with `drag_area` moving at `airspeed` through air of `density`,
+/// against its direction of travel through the air. It can slow an entity of `mass` down to the speed
+/// of the air over `delta` seconds, but never push it backwards, even during long ticks.
+pub fn drag_force(density: f32, airspeed: Vec3, drag_area: f32, mass: f32, delta: f32) -> Vec3 {
+    let speed = airspeed.length();
+    let drag =
+        (0.5 * density * speed * speed * drag_area).min(mass * speed / delta.max(f32::EPSILON));
+    -airspeed.normalize_or_zero() * drag
+}
+
+/// Makes an entity feel the [Atmosphere] of the bodies it flies through. Requires a [Force].
+#[derive(Debug, Clone, Copy, Deserialize, Component, Reflect)]
+pub struct Aerodynamics {
+    /// Drag coefficient times reference area, in square world units. The drag force is
+    /// `0.5 * density * airspeed² * drag_area`, against the direction of travel through the air.
+    pub drag_area: f32,
+    /// Sutton-Graves style constant relating flight conditions to entry heating:
+    /// the heat flux is `heating_coefficient * sqrt(density) * airspeed³`, in watts.
+    pub heating_coefficient: f32,
+}
+
+impl Default for Aerodynamics {
+    fn default() -> Self {
+        Self {
+            drag_area: 1.0,
+            heating_coefficient: 1e-4,
+        }
+    }
+}
+
+/// Flight conditions of an entity with [Aerodynamics], updated every tick by [atmospheric_drag_system].
+/// All zero in vacuum.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+pub struct AtmosphericConditions {
+    /// Density of the surrounding air.
+    pub density: f32,
+    /// Velocity relative to the surrounding air, in world space.
+    pub airspeed: Vec3,
+    /// `0.5 * density * airspeed²`, in pascals.
+    pub dynamic_pressure: f32,
+    /// Heat flux into the entity from compressing the air in front of it, in watts.
+    pub heating: f32,
+}
+
+pub struct AtmospherePlugin;
+
+impl Plugin for AtmospherePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            atmospheric_drag_system
+                .after(thruster_force_system)
+                .in_set(PhysicsSet::Forces),
+        )
+        .register_type::<Atmosphere>()
+        .register_type::<Aerodynamics>()
+        .register_type::<AtmosphericConditions>();
+    }
+}
+
+/// Adds the [drag_force] to the [Force] of every entity with [Aerodynamics] inside an [Atmosphere],
+/// and records the resulting [AtmosphericConditions]. Where atmospheres overlap, the densest one wins.
+/// [Sleeping] entities are skipped.
+#[allow(clippy::type_complexity)]
+pub fn atmospheric_drag_system(
+    mut commands: Commands,
+    time: Res<SimulationTime>,
+    atmospheres: Query<(Entity, &Atmosphere, &CelestialBody)>,
+    frames: FrameQuery,
+    mut bodies: Query<
+        (
+            Entity,
+            &Aerodynamics,
+            &mut Force,
+            Option<&Mass>,
+            Option<&Parent>,
+            Option<&mut AtmosphericConditions>,
+        ),
+        Without<Sleeping>,
+    >,
+) {
+    let atmospheres: Vec<_> = atmospheres
+        .iter()
+        .map(|(entity, atmosphere, body)| (world_motion(entity, &frames), *atmosphere, body.radius))
+        .collect();
+
+    for (entity, aerodynamics, mut force, mass, parent, conditions) in bodies.iter_mut() {
+        let motion = world_motion(entity, &frames);
+
+        let updated = match air_at(&motion, &atmospheres) {
+            Some((density, airspeed)) => {
+                let speed = airspeed.length();
+                let dynamic_pressure = 0.5 * density * speed * speed;
+                let drag = drag_force(
+                    density,
+                    airspeed,
+                    aerodynamics.drag_area,
+                    mass.map(|m| m.0).unwrap_or(1.0),
+                    time.delta,
+                );
+
+                // Force is accumulated in the frame the entity moves in, which is its parent's.
+                let parent_rotation = parent
+                    .map(|p| world_motion(p.get(), &frames).transform.rotation)
+                    .unwrap_or_default();
+                force.0 += parent_rotation.inverse() * drag;
+
+                AtmosphericConditions {
+                    density,
+                    airspeed,
+                    dynamic_pressure,
+                    heating: aerodynamics.heating_coefficient * density.sqrt() * speed.powi(3),
+                }
+            }
+            None => AtmosphericConditions::default(),
+        };
+
+        match conditions {
+            Some(mut conditions) => *conditions = updated,
+            None => {
+                commands.entity(entity).insert(updated);
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use bevy::prelude::*;
+
+use super::shape::Aabb;
+
+/// Sweep-and-prune over the X-axis: returns every pair of entries whose bounding boxes overlap.
+/// Pairs are ordered so the first entity is the smaller one, which keeps them stable across ticks.
+pub fn sweep_and_prune(mut entries: Vec<(Entity, Aabb)>) -> Vec<(Entity, Entity)> {
+    entries.sort_unstable_by(|(_, a), (_, b)| a.min.x.total_cmp(&b.min.x));
+
+    let mut pairs = Vec::new();
+    let mut active: Vec<usize> = Vec::new();
+
+    for (i, (entity, aabb)) in entries.iter().enumerate() {
+        active.retain(|&j| entries[j].1.max.x >= aabb.min.x);
+
+        for &j in active.iter() {
+            let (other, other_aabb) = entries[j];
+            if aabb.intersects(&other_aabb) {
+                pairs.push((other.min(*entity), other.max(*entity)));
+            }
+        }
+
+        active.push(i);
+    }
+
+    pairs
+}
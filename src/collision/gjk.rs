@@ -0,0 +1,486 @@
+//! Narrowphase for arbitrary convex shapes: GJK finds whether two shapes overlap,
+//! EPA then expands GJK's final simplex to find the penetration depth and normal.
+//! [ray_cast] uses the GJK-based ray cast to find where a ray enters a shape.
+
+use bevy::prelude::*;
+
+const MAX_ITERATIONS: usize = 64;
+const EPA_TOLERANCE: f32 = 1e-4;
+/// Relative precision of [ray_cast], as a fraction of the distance to the shape's support points.
+const RAY_TOLERANCE: f32 = 1e-4;
+
+/// Point of contact between two overlapping shapes `a` and `b`.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct Contact {
+    /// World-space point halfway between the deepest points of both shapes.
+    pub point: Vec3,
+    /// Unit vector pointing from `a` towards `b`. Moving `b` by `normal * depth` separates the shapes.
+    pub normal: Vec3,
+    /// How far the shapes overlap along the normal.
+    pub depth: f32,
+}
+
+/// Vertex of the Minkowski difference `a - b`, together with the points on `a` and `b` it came from.
+#[derive(Debug, Clone, Copy)]
+struct SupportPoint {
+    point: Vec3,
+    a: Vec3,
+    b: Vec3,
+}
+
+impl SupportPoint {
+    fn new(
+        direction: Vec3,
+        support_a: &impl Fn(Vec3) -> Vec3,
+        support_b: &impl Fn(Vec3) -> Vec3,
+    ) -> Self {
+        let a = support_a(direction);
+        let b = support_b(-direction);
+        Self { point: a - b, a, b }
+    }
+}
+
+/// Finds the contact between two convex shapes given by their world-space support functions,
+/// or `None` if they don't overlap.
+pub fn contact(
+    support_a: impl Fn(Vec3) -> Vec3,
+    support_b: impl Fn(Vec3) -> Vec3,
+) -> Option<Contact> {
+    let simplex = gjk(&support_a, &support_b)?;
+    epa(simplex, &support_a, &support_b)
+}
+
+/// Where a ray first enters a convex shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayIntersection {
+    /// Distance travelled along the ray's unit direction.
+    pub distance: f32,
+    /// World-space point where the ray enters the shape.
+    pub point: Vec3,
+    /// Outward unit normal of the shape at `point`. Opposes the ray if it starts inside the shape.
+    pub normal: Vec3,
+}
+
+/// Casts a ray from `origin` along the unit vector `direction` against a convex shape given by its
+/// world-space support function, using the GJK ray cast by van den Bergen. Returns `None` if the ray
+/// misses, or hits further than `max_distance` away.
+pub fn ray_cast(
+    support: impl Fn(Vec3) -> Vec3,
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+) -> Option<RayIntersection> {
+    let mut distance = 0.0;
+    let mut position = origin;
+    let mut normal = Vec3::ZERO;
+    let mut simplex: Vec<Vec3> = Vec::new();
+    let mut closest = position - support(-direction);
+
+    for _ in 0..MAX_ITERATIONS {
+        let point = support(closest);
+        let towards = position - point;
+
+        if closest.dot(towards) > 0.0 {
+            // `closest` separates the ray's position from the shape, so advance up to that plane.
+            if closest.dot(direction) >= 0.0 {
+                return None;
+            }
+            distance -= closest.dot(towards) / closest.dot(direction);
+            if distance > max_distance {
+                return None;
+            }
+            position = origin + direction * distance;
+            normal = closest;
+        }
+
+        simplex.push(point);
+        let (found, kept) = closest_to_origin(simplex.iter().map(|point| position - *point));
+        simplex = kept.into_iter().map(|i| simplex[i]).collect();
+        closest = found;
+
+        let scale = simplex
+            .iter()
+            .map(|point| (position - *point).length_squared())
+            .fold(0.0, f32::max);
+        if simplex.len() == 4 || closest.length_squared() <= RAY_TOLERANCE * RAY_TOLERANCE * scale {
+            break;
+        }
+    }
+
+    Some(RayIntersection {
+        distance,
+        point: position,
+        normal: normal.try_normalize().unwrap_or(-direction),
+    })
+}
+
+/// Point of the convex hull of up to four `points` closest to the origin, together with the indices
+/// of the smallest subset of `points` whose hull still contains it.
+fn closest_to_origin(points: impl Iterator<Item = Vec3>) -> (Vec3, Vec<usize>) {
+    let points: Vec<Vec3> = points.collect();
+    let mut best = (Vec3::splat(f32::INFINITY), Vec::new());
+
+    // The closest point lies inside exactly one face of the simplex, and projecting the origin onto the
+    // affine hull of any face it lies inside gives a point at least as far away, so trying them all works.
+    for subset in 1..(1usize << points.len()) {
+        let indices: Vec<usize> = (0..points.len())
+            .filter(|i| subset & (1 << i) != 0)
+            .collect();
+        let Some(projected) =
+            project_origin(&indices.iter().map(|i| points[*i]).collect::<Vec<_>>())
+        else {
+            continue;
+        };
+
+        if projected.length_squared() < best.0.length_squared() {
+            best = (projected, indices);
+        }
+    }
+
+    best
+}
+
+/// Projection of the origin onto the affine hull of `points`, if it lies strictly inside their hull.
+fn project_origin(points: &[Vec3]) -> Option<Vec3> {
+    let base = points[0];
+    let edges: Vec<Vec3> = points[1..].iter().map(|point| *point - base).collect();
+
+    // Solve for the weights of the edges minimising `|base + Σ weight * edge|`.
+    let weights: Vec<f32> = match edges.as_slice() {
+        [] => Vec::new(),
+        [a] => {
+            let length = a.length_squared();
+            if length <= f32::EPSILON * base.length_squared() {
+                return None;
+            }
+            vec![-a.dot(base) / length]
+        }
+        [a, b] => {
+            let gram = Mat2::from_cols(
+                Vec2::new(a.dot(*a), a.dot(*b)),
+                Vec2::new(a.dot(*b), b.dot(*b)),
+            );
+            if gram.determinant() <= 1e-6 * (gram.x_axis.x + gram.y_axis.y).powi(2) {
+                return None;
+            }
+            let solved = gram.inverse() * -Vec2::new(a.dot(base), b.dot(base));
+            vec![solved.x, solved.y]
+        }
+        [a, b, c] => {
+            let gram = Mat3::from_cols(*a, *b, *c).transpose() * Mat3::from_cols(*a, *b, *c);
+            let trace = gram.x_axis.x + gram.y_axis.y + gram.z_axis.z;
+            if gram.determinant() <= 1e-6 * trace.powi(3) {
+                return None;
+            }
+            let solved = gram.inverse() * -Vec3::new(a.dot(base), b.dot(base), c.dot(base));
+            solved.to_array().to_vec()
+        }
+        _ => return None,
+    };
+
+    let rest = 1.0 - weights.iter().sum::<f32>();
+    if rest <= 0.0 || weights.iter().any(|weight| *weight <= 0.0) {
+        return None;
+    }
+
+    Some(
+        edges
+            .iter()
+            .zip(weights.iter())
+            .fold(base, |sum, (edge, weight)| sum + *edge * *weight),
+    )
+}
+
+fn gjk(
+    support_a: &impl Fn(Vec3) -> Vec3,
+    support_b: &impl Fn(Vec3) -> Vec3,
+) -> Option<Vec<SupportPoint>> {
+    let first = SupportPoint::new(Vec3::X, support_a, support_b);
+    let mut simplex = vec![first];
+    let mut direction = -first.point;
+
+    for _ in 0..MAX_ITERATIONS {
+        if direction.length_squared() < f32::EPSILON {
+            // The origin lies on the simplex, so the shapes are touching.
+            return Some(simplex);
+        }
+
+        let next = SupportPoint::new(direction, support_a, support_b);
+        if next.point.dot(direction) < 0.0 {
+            return None;
+        }
+
+        simplex.push(next);
+        if update_simplex(&mut simplex, &mut direction) {
+            return Some(simplex);
+        }
+    }
+
+    None
+}
+
+/// Reduces the simplex to the feature closest to the origin and points `direction` from that
+/// feature towards the origin. Returns `true` once the simplex is a tetrahedron enclosing the origin.
+/// The most recently added point is always last.
+fn update_simplex(simplex: &mut Vec<SupportPoint>, direction: &mut Vec3) -> bool {
+    match simplex.len() {
+        2 => {
+            let (b, a) = (simplex[0], simplex[1]);
+            let ab = b.point - a.point;
+            let ao = -a.point;
+            if ab.dot(ao) > 0.0 {
+                *direction = ab.cross(ao).cross(ab);
+            } else {
+                *simplex = vec![a];
+                *direction = ao;
+            }
+            false
+        }
+        3 => {
+            let (c, b, a) = (simplex[0], simplex[1], simplex[2]);
+            triangle(simplex, direction, a, b, c);
+            false
+        }
+        4 => {
+            let (d, c, b, a) = (simplex[0], simplex[1], simplex[2], simplex[3]);
+            let ao = -a.point;
+            let abc = (b.point - a.point).cross(c.point - a.point);
+            let acd = (c.point - a.point).cross(d.point - a.point);
+            let adb = (d.point - a.point).cross(b.point - a.point);
+
+            if abc.dot(ao) > 0.0 {
+                triangle(simplex, direction, a, b, c);
+                false
+            } else if acd.dot(ao) > 0.0 {
+                triangle(simplex, direction, a, c, d);
+                false
+            } else if adb.dot(ao) > 0.0 {
+                triangle(simplex, direction, a, d, b);
+                false
+            } else {
+                true
+            }
+        }
+        _ => unreachable!("the GJK simplex has between two and four points"),
+    }
+}
+
+/// Simplex update for the triangle `abc`, where `a` is the newest point. Keeps the winding such
+/// that a tetrahedron built on top of it has outward-facing normals.
+fn triangle(
+    simplex: &mut Vec<SupportPoint>,
+    direction: &mut Vec3,
+    a: SupportPoint,
+    b: SupportPoint,
+    c: SupportPoint,
+) {
+    let ab = b.point - a.point;
+    let ac = c.point - a.point;
+    let ao = -a.point;
+    let abc = ab.cross(ac);
+
+    if abc.cross(ac).dot(ao) > 0.0 {
+        if ac.dot(ao) > 0.0 {
+            *simplex = vec![c, a];
+            *direction = ac.cross(ao).cross(ac);
+        } else {
+            line(simplex, direction, a, b);
+        }
+    } else if ab.cross(abc).dot(ao) > 0.0 {
+        line(simplex, direction, a, b);
+    } else if abc.dot(ao) > 0.0 {
+        *simplex = vec![c, b, a];
+        *direction = abc;
+    } else {
+        *simplex = vec![b, c, a];
+        *direction = -abc;
+    }
+}
+
+fn line(simplex: &mut Vec<SupportPoint>, direction: &mut Vec3, a: SupportPoint, b: SupportPoint) {
+    let ab = b.point - a.point;
+    let ao = -a.point;
+    if ab.dot(ao) > 0.0 {
+        *simplex = vec![b, a];
+        *direction = ab.cross(ao).cross(ab);
+    } else {
+        *simplex = vec![a];
+        *direction = ao;
+    }
+}
+
+/// Grows a degenerate simplex from GJK (the origin was on a vertex, edge or face)
+/// into a tetrahedron by adding support points along directions it doesn't span yet.
+fn complete_simplex(
+    simplex: &mut Vec<SupportPoint>,
+    support_a: &impl Fn(Vec3) -> Vec3,
+    support_b: &impl Fn(Vec3) -> Vec3,
+) -> bool {
+    const AXES: [Vec3; 6] = [
+        Vec3::X,
+        Vec3::Y,
+        Vec3::Z,
+        Vec3::NEG_X,
+        Vec3::NEG_Y,
+        Vec3::NEG_Z,
+    ];
+
+    let spans = |simplex: &[SupportPoint], candidate: Vec3| -> bool {
+        let origin = simplex[0].point;
+        match simplex.len() {
+            1 => (candidate - origin).length_squared() > f32::EPSILON,
+            2 => {
+                (simplex[1].point - origin)
+                    .cross(candidate - origin)
+                    .length_squared()
+                    > f32::EPSILON
+            }
+            _ => {
+                let normal = (simplex[1].point - origin).cross(simplex[2].point - origin);
+                normal.dot(candidate - origin).abs() > f32::EPSILON
+            }
+        }
+    };
+
+    while simplex.len() < 4 {
+        let normal = match simplex.len() {
+            3 => Some(
+                (simplex[1].point - simplex[0].point).cross(simplex[2].point - simplex[0].point),
+            ),
+            _ => None,
+        };
+
+        let candidate = normal
+            .into_iter()
+            .flat_map(|n| [n, -n])
+            .chain(AXES)
+            .map(|direction| SupportPoint::new(direction, support_a, support_b))
+            .find(|candidate| spans(simplex, candidate.point));
+
+        match candidate {
+            Some(candidate) => simplex.push(candidate),
+            // The Minkowski difference is flat, so the shapes only graze each other.
+            None => return false,
+        }
+    }
+
+    true
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Face {
+    indices: [usize; 3],
+    normal: Vec3,
+    distance: f32,
+}
+
+impl Face {
+    fn new(vertices: &[SupportPoint], indices: [usize; 3]) -> Option<Self> {
+        let [a, b, c] = indices.map(|i| vertices[i].point);
+        let normal = (b - a).cross(c - a).try_normalize()?;
+        Some(Self {
+            indices,
+            normal,
+            distance: normal.dot(a),
+        })
+    }
+}
+
+fn epa(
+    mut simplex: Vec<SupportPoint>,
+    support_a: &impl Fn(Vec3) -> Vec3,
+    support_b: &impl Fn(Vec3) -> Vec3,
+) -> Option<Contact> {
+    if !complete_simplex(&mut simplex, support_a, support_b) {
+        return None;
+    }
+
+    // Wind the tetrahedron so every face normal points away from the opposite vertex.
+    let [a, b, c, d] = [0, 1, 2, 3].map(|i| simplex[i].point);
+    if (b - a).cross(c - a).dot(d - a) > 0.0 {
+        simplex.swap(1, 2);
+    }
+
+    let mut vertices = simplex;
+    let mut faces: Vec<Face> = [[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]]
+        .into_iter()
+        .filter_map(|indices| Face::new(&vertices, indices))
+        .collect();
+
+    for _ in 0..MAX_ITERATIONS {
+        let closest = *faces
+            .iter()
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))?;
+
+        let next = SupportPoint::new(closest.normal, support_a, support_b);
+        if next.point.dot(closest.normal) - closest.distance < EPA_TOLERANCE {
+            return Some(contact_on_face(&vertices, &closest));
+        }
+
+        // Remove every face the new point can see, and stitch the hole's rim to the new point.
+        let mut horizon: Vec<[usize; 2]> = Vec::new();
+        faces.retain(|face| {
+            let visible = face
+                .normal
+                .dot(next.point - vertices[face.indices[0]].point)
+                > 0.0;
+            if visible {
+                let [i, j, k] = face.indices;
+                for edge in [[i, j], [j, k], [k, i]] {
+                    if let Some(shared) = horizon.iter().position(|e| *e == [edge[1], edge[0]]) {
+                        horizon.swap_remove(shared);
+                    } else {
+                        horizon.push(edge);
+                    }
+                }
+            }
+            !visible
+        });
+
+        let index = vertices.len();
+        vertices.push(next);
+        faces.extend(
+            horizon
+                .into_iter()
+                .filter_map(|[i, j]| Face::new(&vertices, [i, j, index])),
+        );
+    }
+
+    let closest = faces
+        .iter()
+        .min_by(|a, b| a.distance.total_cmp(&b.distance))?;
+    Some(contact_on_face(&vertices, closest))
+}
+
+/// Projects the origin onto `face` and maps the result back onto both shapes.
+fn contact_on_face(vertices: &[SupportPoint], face: &Face) -> Contact {
+    let [a, b, c] = face.indices.map(|i| vertices[i]);
+    let projected = face.normal * face.distance;
+    let [u, v, w] = barycentric(projected, a.point, b.point, c.point);
+
+    let on_a = a.a * u + b.a * v + c.a * w;
+    let on_b = a.b * u + b.b * v + c.b * w;
+
+    Contact {
+        point: (on_a + on_b) * 0.5,
+        normal: face.normal,
+        depth: face.distance.max(0.0),
+    }
+}
+
+fn barycentric(p: Vec3, a: Vec3, b: Vec3, c: Vec3) -> [f32; 3] {
+    let (v0, v1, v2) = (b - a, c - a, p - a);
+    let d00 = v0.dot(v0);
+    let d01 = v0.dot(v1);
+    let d11 = v1.dot(v1);
+    let d20 = v2.dot(v0);
+    let d21 = v2.dot(v1);
+    let denominator = d00 * d11 - d01 * d01;
+    if denominator.abs() < f32::EPSILON {
+        return [1.0, 0.0, 0.0];
+    }
+
+    let v = (d11 * d20 - d01 * d21) / denominator;
+    let w = (d00 * d21 - d01 * d20) / denominator;
+    [1.0 - v - w, v, w]
+}
@@ -0,0 +1,270 @@
+use bevy::{
+    math::Affine3A,
+    prelude::*,
+    render::mesh::VertexAttributeValues,
+    transform::systems::{propagate_transforms, sync_simple_transforms},
+    utils::HashMap,
+};
+
+use crate::physics::{AngularVelocity, PhysicsSet, Velocity};
+
+mod broadphase;
+mod gjk;
+mod query;
+mod shape;
+mod solver;
+
+pub use broadphase::sweep_and_prune;
+pub use gjk::{contact, ray_cast, Contact, RayIntersection};
+pub use query::{RayHit, SpatialQuery, SpatialQueryFilter};
+pub use shape::{Aabb, Collider};
+pub use solver::{contact_solver_system, ContactSolverSettings, PhysicsMaterial};
+
+/// Replaced by a [Collider::ConvexHull] around all meshes below the entity once they have loaded.
+/// Meant for entities whose model is a GLB scene, such as the ships or the station's pipe parts.
+#[derive(Debug, Default, Clone, Component, Reflect)]
+pub struct GenerateConvexHull {
+    /// Descendants whose [Name] starts with one of these prefixes are left out of the hull,
+    /// for example animated thruster flames.
+    pub exclude: Vec<String>,
+}
+
+/// Sent in the tick two colliders start overlapping. `a` is always the smaller entity.
+#[derive(Debug, Clone, Event)]
+pub struct CollisionStarted {
+    pub a: Entity,
+    pub b: Entity,
+    pub contact: Contact,
+}
+
+/// Sent in the first tick two previously overlapping colliders no longer overlap.
+#[derive(Debug, Clone, Event)]
+pub struct CollisionEnded {
+    pub a: Entity,
+    pub b: Entity,
+}
+
+/// Every pair of colliders overlapping in the current physics tick, keyed by `(a, b)` with `a < b`.
+#[derive(Debug, Default, Resource)]
+pub struct Collisions {
+    contacts: HashMap<(Entity, Entity), Contact>,
+}
+
+impl Collisions {
+    /// Contact between `a` and `b`, with the normal pointing from `a` towards `b`.
+    pub fn get(&self, a: Entity, b: Entity) -> Option<Contact> {
+        if a < b {
+            self.contacts.get(&(a, b)).copied()
+        } else {
+            self.contacts.get(&(b, a)).map(|contact| Contact {
+                normal: -contact.normal,
+                ..*contact
+            })
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Entity, &Contact)> {
+        self.contacts
+            .iter()
+            .map(|((a, b), contact)| (*a, *b, contact))
+    }
+
+    /// Every entity currently colliding with `entity`.
+    pub fn colliding_with(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.contacts.keys().filter_map(move |(a, b)| {
+            if *a == entity {
+                Some(*b)
+            } else if *b == entity {
+                Some(*a)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Entities which move on their own, see [rigid_body].
+pub type BodyQuery<'w, 's> = Query<'w, 's, (), Or<(With<Velocity>, With<AngularVelocity>)>>;
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Collisions>()
+            .init_resource::<ContactSolverSettings>()
+            .add_event::<CollisionStarted>()
+            .add_event::<CollisionEnded>()
+            .add_systems(
+                FixedUpdate,
+                // GlobalTransforms are otherwise only propagated once per frame, after all ticks.
+                (
+                    sync_simple_transforms,
+                    propagate_transforms,
+                    collision_detection_system,
+                )
+                    .chain()
+                    .in_set(PhysicsSet::Collide),
+            )
+            .add_systems(FixedUpdate, contact_solver_system.in_set(PhysicsSet::Solve))
+            .add_systems(Update, generate_convex_hull_system)
+            .register_type::<Collider>()
+            .register_type::<GenerateConvexHull>()
+            .register_type::<PhysicsMaterial>()
+            .register_type::<ContactSolverSettings>();
+    }
+}
+
+/// Entity which moves the collider: the closest ancestor-or-self with a [Velocity] or
+/// [AngularVelocity], or the root of the hierarchy if nothing above the collider moves.
+/// Colliders belonging to the same body never collide with each other.
+pub fn rigid_body(entity: Entity, parents: &Query<&Parent>, bodies: &BodyQuery) -> Entity {
+    let mut current = entity;
+    loop {
+        if bodies.contains(current) {
+            return current;
+        }
+        match parents.get(current) {
+            Ok(parent) => current = parent.get(),
+            Err(_) => return current,
+        }
+    }
+}
+
+/// Finds all overlapping [Collider]s with a sweep-and-prune broadphase followed by GJK/EPA,
+/// updates [Collisions] and sends [CollisionStarted] and [CollisionEnded] for pairs which changed.
+pub fn collision_detection_system(
+    mut collisions: ResMut<Collisions>,
+    mut started: EventWriter<CollisionStarted>,
+    mut ended: EventWriter<CollisionEnded>,
+    colliders: Query<(Entity, &Collider, &GlobalTransform)>,
+    parents: Query<&Parent>,
+    bodies: BodyQuery,
+) {
+    let entries = colliders
+        .iter()
+        .map(|(entity, collider, transform)| (entity, collider.aabb(&transform.affine())))
+        .collect();
+
+    let mut contacts = HashMap::new();
+    for (a, b) in sweep_and_prune(entries) {
+        if rigid_body(a, &parents, &bodies) == rigid_body(b, &parents, &bodies) {
+            continue;
+        }
+
+        let Ok([(_, collider_a, transform_a), (_, collider_b, transform_b)]) =
+            colliders.get_many([a, b])
+        else {
+            continue;
+        };
+
+        let (affine_a, affine_b) = (transform_a.affine(), transform_b.affine());
+        let found = contact(
+            |direction| collider_a.support(&affine_a, direction),
+            |direction| collider_b.support(&affine_b, direction),
+        );
+
+        if let Some(contact) = found {
+            contacts.insert((a, b), contact);
+        }
+    }
+
+    for (&(a, b), contact) in contacts.iter() {
+        if !collisions.contacts.contains_key(&(a, b)) {
+            started.send(CollisionStarted {
+                a,
+                b,
+                contact: *contact,
+            });
+        }
+    }
+
+    for &(a, b) in collisions.contacts.keys() {
+        if !contacts.contains_key(&(a, b)) {
+            ended.send(CollisionEnded { a, b });
+        }
+    }
+
+    collisions.contacts = contacts;
+}
+
+/// Builds the [Collider] of every entity with [GenerateConvexHull] once its scene has spawned
+/// and all of its meshes have loaded. Vertices are brought into the entity's local space through
+/// the descendants' [Transform]s, since their [GlobalTransform]s may not be propagated yet.
+#[allow(clippy::type_complexity)]
+pub fn generate_convex_hull_system(
+    mut commands: Commands,
+    meshes: Res<Assets<Mesh>>,
+    pending: Query<(Entity, &GenerateConvexHull)>,
+    children: Query<&Children>,
+    nodes: Query<(&Transform, Option<&Handle<Mesh>>, Option<&Name>)>,
+) {
+    for (entity, settings) in pending.iter() {
+        let mut points = Vec::new();
+        let mut loaded = true;
+
+        let Ok(child_entities) = children.get(entity) else {
+            continue;
+        };
+
+        let mut stack: Vec<(Entity, Affine3A)> = child_entities
+            .iter()
+            .map(|child| (*child, Affine3A::IDENTITY))
+            .collect();
+
+        while let Some((node, parent_affine)) = stack.pop() {
+            let Ok((transform, mesh, name)) = nodes.get(node) else {
+                continue;
+            };
+
+            let excluded = name.is_some_and(|name| {
+                settings
+                    .exclude
+                    .iter()
+                    .any(|prefix| name.as_str().starts_with(prefix.as_str()))
+            });
+            if excluded {
+                continue;
+            }
+
+            let affine = parent_affine * transform.compute_affine();
+
+            if let Some(handle) = mesh {
+                match meshes
+                    .get(handle)
+                    .and_then(|mesh| mesh.attribute(Mesh::ATTRIBUTE_POSITION))
+                {
+                    Some(VertexAttributeValues::Float32x3(positions)) => points.extend(
+                        positions
+                            .iter()
+                            .map(|position| affine.transform_point3(Vec3::from(*position))),
+                    ),
+                    Some(_) => {}
+                    None => loaded = false,
+                }
+            }
+
+            if let Ok(grandchildren) = children.get(node) {
+                stack.extend(grandchildren.iter().map(|child| (*child, affine)));
+            }
+        }
+
+        if !loaded || points.is_empty() {
+            continue;
+        }
+
+        let collider = Collider::convex_hull(&points);
+        if let Collider::ConvexHull { points: hull } = &collider {
+            debug!(
+                "generated convex hull for {:?} with {} of {} points",
+                entity,
+                hull.len(),
+                points.len()
+            );
+        }
+
+        commands
+            .entity(entity)
+            .insert(collider)
+            .remove::<GenerateConvexHull>();
+    }
+}
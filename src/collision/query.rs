@@ -0,0 +1,231 @@
+use bevy::{ecs::system::SystemParam, math::Affine3A, prelude::*};
+
+use super::{contact, ray_cast, rigid_body, Aabb, BodyQuery, Collider, RayIntersection};
+use crate::trigger::TriggerLayers;
+
+/// Which colliders a [SpatialQuery] considers.
+#[derive(Debug, Default, Clone)]
+pub struct SpatialQueryFilter {
+    /// Only colliders whose own [TriggerLayers], or their rigid body's, share a bit with this mask.
+    /// `None` accepts every collider, with or without layers.
+    pub mask: Option<u32>,
+    /// Rigid bodies to ignore along with all of their colliders, for example the ship doing the query.
+    pub exclude: Vec<Entity>,
+    /// Test against the colliders' world-space bounding boxes instead of their exact shapes.
+    /// Cheaper, and good enough for coarse checks like whether anything is in the way at all.
+    pub bounding_volumes: bool,
+}
+
+/// Result of a ray or shape cast.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    /// Collider which was hit.
+    pub entity: Entity,
+    /// Rigid body the collider belongs to, see [rigid_body].
+    pub body: Entity,
+    /// Distance travelled along the cast's direction until the hit.
+    pub distance: f32,
+    /// World-space point where the collider was hit.
+    pub point: Vec3,
+    /// Outward unit normal of the collider at `point`.
+    pub normal: Vec3,
+}
+
+/// Ray casts, sphere casts and overlap tests against every [Collider] in the world, such as ships,
+/// stations and celestial bodies. Works on [GlobalTransform]s, so it needs no renderer, but sees
+/// colliders where they were when transforms were last propagated.
+///
+/// Directions passed to the casts don't need to be normalized.
+#[derive(SystemParam)]
+pub struct SpatialQuery<'w, 's> {
+    colliders: Query<'w, 's, (Entity, &'static Collider, &'static GlobalTransform)>,
+    layers: Query<'w, 's, &'static TriggerLayers>,
+    parents: Query<'w, 's, &'static Parent>,
+    bodies: BodyQuery<'w, 's>,
+}
+
+impl<'w, 's> SpatialQuery<'w, 's> {
+    /// Closest collider hit by the ray from `origin` along `direction`, at most `max_distance` away.
+    pub fn cast_ray(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        filter: &SpatialQueryFilter,
+    ) -> Option<RayHit> {
+        self.cast_sphere(origin, 0.0, direction, max_distance, filter)
+    }
+
+    /// Every collider hit by the ray from `origin` along `direction` within `max_distance`, closest first.
+    pub fn cast_ray_all(
+        &self,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        filter: &SpatialQueryFilter,
+    ) -> Vec<RayHit> {
+        let mut hits = self.hits(origin, 0.0, direction, max_distance, filter);
+        hits.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        hits
+    }
+
+    /// First collider hit by a sphere of `radius` swept from `origin` along `direction`,
+    /// at most `max_distance` away. The hit's point lies on the collider's surface.
+    pub fn cast_sphere(
+        &self,
+        origin: Vec3,
+        radius: f32,
+        direction: Vec3,
+        max_distance: f32,
+        filter: &SpatialQueryFilter,
+    ) -> Option<RayHit> {
+        self.hits(origin, radius, direction, max_distance, filter)
+            .into_iter()
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    /// Every collider overlapping the sphere of `radius` around `center`.
+    pub fn overlap_sphere(
+        &self,
+        center: Vec3,
+        radius: f32,
+        filter: &SpatialQueryFilter,
+    ) -> Vec<Entity> {
+        self.overlap_collider(
+            &Collider::Sphere { radius },
+            &Affine3A::from_translation(center),
+            filter,
+        )
+    }
+
+    /// Every collider overlapping `shape` placed at `transform`.
+    pub fn overlap_collider(
+        &self,
+        shape: &Collider,
+        transform: &Affine3A,
+        filter: &SpatialQueryFilter,
+    ) -> Vec<Entity> {
+        let bounds = shape.aabb(transform);
+
+        self.candidates(filter)
+            .filter(|(_, collider, affine)| {
+                let aabb = collider.aabb(affine);
+                if !aabb.intersects(&bounds) {
+                    return false;
+                }
+
+                filter.bounding_volumes
+                    || contact(
+                        |direction| collider.support(affine, direction),
+                        |direction| shape.support(transform, direction),
+                    )
+                    .is_some()
+            })
+            .map(|(entity, ..)| entity)
+            .collect()
+    }
+
+    fn hits(
+        &self,
+        origin: Vec3,
+        radius: f32,
+        direction: Vec3,
+        max_distance: f32,
+        filter: &SpatialQueryFilter,
+    ) -> Vec<RayHit> {
+        let Some(direction) = direction.try_normalize() else {
+            return Vec::new();
+        };
+
+        self.candidates(filter)
+            .filter_map(|(entity, collider, affine)| {
+                let aabb = collider.aabb(&affine);
+                let inflated = Aabb {
+                    min: aabb.min - Vec3::splat(radius),
+                    max: aabb.max + Vec3::splat(radius),
+                };
+                let entry = ray_aabb(origin, direction, max_distance, &inflated)?;
+
+                let intersection = if filter.bounding_volumes {
+                    entry
+                } else {
+                    ray_cast(
+                        |d| collider.support(&affine, d) + d.normalize_or_zero() * radius,
+                        origin,
+                        direction,
+                        max_distance,
+                    )?
+                };
+
+                Some(RayHit {
+                    entity,
+                    body: rigid_body(entity, &self.parents, &self.bodies),
+                    distance: intersection.distance,
+                    point: intersection.point - intersection.normal * radius,
+                    normal: intersection.normal,
+                })
+            })
+            .collect()
+    }
+
+    /// Colliders passing `filter`, with their world-space transforms.
+    fn candidates<'a>(
+        &'a self,
+        filter: &'a SpatialQueryFilter,
+    ) -> impl Iterator<Item = (Entity, &'a Collider, Affine3A)> + 'a {
+        self.colliders
+            .iter()
+            .filter(move |(entity, ..)| {
+                let body = rigid_body(*entity, &self.parents, &self.bodies);
+                if filter.exclude.contains(&body) {
+                    return false;
+                }
+
+                match filter.mask {
+                    Some(mask) => [*entity, body]
+                        .iter()
+                        .any(|e| self.layers.get(*e).is_ok_and(|layers| layers.0 & mask != 0)),
+                    None => true,
+                }
+            })
+            .map(|(entity, collider, transform)| (entity, collider, transform.affine()))
+    }
+}
+
+/// Where the ray from `origin` along the unit vector `direction` enters `aabb`, using the slab method.
+fn ray_aabb(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    aabb: &Aabb,
+) -> Option<RayIntersection> {
+    let inverse = direction.recip();
+    let near = (aabb.min - origin) * inverse;
+    let far = (aabb.max - origin) * inverse;
+    let (entry, exit) = (near.min(far), near.max(far));
+
+    // Axes the ray runs parallel to produce NaNs, which `max_element` and `min_element` skip.
+    let (enter, leave) = (entry.max_element(), exit.min_element());
+    if enter > leave || leave < 0.0 || enter > max_distance {
+        return None;
+    }
+
+    let distance = enter.max(0.0);
+    let axis = if enter == entry.x {
+        Vec3::X
+    } else if enter == entry.y {
+        Vec3::Y
+    } else {
+        Vec3::Z
+    };
+
+    Some(RayIntersection {
+        distance,
+        point: origin + direction * distance,
+        normal: if enter < 0.0 {
+            -direction
+        } else {
+            -axis * direction.dot(axis).signum()
+        },
+    })
+}
@@ -0,0 +1,126 @@
+use bevy::{math::Affine3A, prelude::*};
+
+/// Number of directions sampled when reducing a point cloud to an approximate convex hull.
+const HULL_DIRECTIONS: usize = 128;
+
+/// Convex collision shape of an entity, in the entity's local space.
+/// Collision detection works on the shape's support function, so any of these can collide with any other.
+#[derive(Debug, Clone, Component, Reflect)]
+pub enum Collider {
+    Sphere {
+        radius: f32,
+    },
+    /// Capsule along the local Y-axis. The total height is `2 * (half_height + radius)`.
+    Capsule {
+        half_height: f32,
+        radius: f32,
+    },
+    Cuboid {
+        half_extents: Vec3,
+    },
+    /// Convex hull of a set of points, see [Collider::convex_hull].
+    ConvexHull {
+        points: Vec<Vec3>,
+    },
+}
+
+impl Default for Collider {
+    fn default() -> Self {
+        Collider::Sphere { radius: 0.5 }
+    }
+}
+
+/// Axis-aligned bounding box in world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    pub fn contains(&self, point: Vec3) -> bool {
+        self.min.cmple(point).all() && point.cmple(self.max).all()
+    }
+}
+
+impl Collider {
+    /// Builds a [Collider::ConvexHull] approximating the convex hull of `points`.
+    /// Only the points which are extreme along one of a fixed set of evenly distributed directions
+    /// are kept, which bounds the cost of the support function for detailed meshes.
+    pub fn convex_hull(points: &[Vec3]) -> Self {
+        let mut hull: Vec<Vec3> = fibonacci_directions(HULL_DIRECTIONS)
+            .filter_map(|direction| {
+                points
+                    .iter()
+                    .copied()
+                    .max_by(|a, b| a.dot(direction).total_cmp(&b.dot(direction)))
+            })
+            .collect();
+
+        hull.sort_by(|a, b| {
+            a.x.total_cmp(&b.x)
+                .then(a.y.total_cmp(&b.y))
+                .then(a.z.total_cmp(&b.z))
+        });
+        hull.dedup();
+
+        Collider::ConvexHull { points: hull }
+    }
+
+    /// Furthest point of the shape along `direction`, in local space.
+    pub fn local_support(&self, direction: Vec3) -> Vec3 {
+        match self {
+            Collider::Sphere { radius } => direction.normalize_or_zero() * *radius,
+            Collider::Capsule {
+                half_height,
+                radius,
+            } => {
+                let tip = Vec3::Y * half_height.copysign(direction.y);
+                tip + direction.normalize_or_zero() * *radius
+            }
+            Collider::Cuboid { half_extents } => {
+                Vec3::select(direction.cmplt(Vec3::ZERO), -*half_extents, *half_extents)
+            }
+            Collider::ConvexHull { points } => points
+                .iter()
+                .copied()
+                .max_by(|a, b| a.dot(direction).total_cmp(&b.dot(direction)))
+                .unwrap_or(Vec3::ZERO),
+        }
+    }
+
+    /// Furthest point of the shape along the world-space `direction` when placed at `transform`.
+    /// Works for any affine transform, including non-uniform scale.
+    pub fn support(&self, transform: &Affine3A, direction: Vec3) -> Vec3 {
+        let local_direction = transform.matrix3.transpose().mul_vec3(direction);
+        transform.transform_point3(self.local_support(local_direction))
+    }
+
+    /// World-space bounding box of the shape when placed at `transform`.
+    pub fn aabb(&self, transform: &Affine3A) -> Aabb {
+        let axis = |direction: Vec3| self.support(transform, direction);
+        Aabb {
+            min: Vec3::new(
+                axis(Vec3::NEG_X).x,
+                axis(Vec3::NEG_Y).y,
+                axis(Vec3::NEG_Z).z,
+            ),
+            max: Vec3::new(axis(Vec3::X).x, axis(Vec3::Y).y, axis(Vec3::Z).z),
+        }
+    }
+}
+
+/// Roughly evenly distributed unit vectors on the sphere.
+pub(crate) fn fibonacci_directions(count: usize) -> impl Iterator<Item = Vec3> {
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    (0..count).map(move |i| {
+        let y = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+        let radius = (1.0 - y * y).sqrt();
+        let (sin, cos) = (golden_angle * i as f32).sin_cos();
+        Vec3::new(cos * radius, y, sin * radius)
+    })
+}
@@ -0,0 +1,314 @@
+use bevy::{math::Affine3A, prelude::*, utils::HashMap};
+
+use super::{rigid_body, BodyQuery, Collisions, Contact};
+use crate::{
+    kinematics::{world_motion, FrameQuery, Motion},
+    physics::{AngularVelocity, Inertia, Mass, OnRails, Velocity},
+    sleep::Sleeping,
+};
+
+/// Surface properties used when resolving contacts. Looked up on the collider first,
+/// then on its rigid body, falling back to the default.
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+pub struct PhysicsMaterial {
+    /// Fraction of the approach speed kept after a bounce: `0.0` doesn't bounce at all, `1.0` is perfectly elastic.
+    pub restitution: f32,
+    /// Coulomb friction coefficient. The friction impulse is at most this times the normal impulse.
+    pub friction: f32,
+}
+
+impl Default for PhysicsMaterial {
+    fn default() -> Self {
+        Self {
+            restitution: 0.2,
+            friction: 0.5,
+        }
+    }
+}
+
+impl PhysicsMaterial {
+    /// Material of a contact between two surfaces: the bouncier of the two restitutions,
+    /// and the geometric mean of the friction coefficients.
+    pub fn combine(&self, other: &Self) -> Self {
+        Self {
+            restitution: self.restitution.max(other.restitution),
+            friction: (self.friction * other.friction).sqrt(),
+        }
+    }
+}
+
+/// Tuning of the [contact_solver_system].
+#[derive(Debug, Clone, Resource, Reflect)]
+pub struct ContactSolverSettings {
+    /// Number of passes over all contacts per tick. More passes settle stacks of touching bodies better.
+    pub iterations: u32,
+    /// Fraction of the penetration beyond [ContactSolverSettings::slop] removed every tick.
+    pub correction: f32,
+    /// Penetration depth which is tolerated, so resting contacts don't jitter in and out of contact.
+    pub slop: f32,
+    /// Approach speeds below this don't bounce, so bodies can come to rest.
+    pub restitution_threshold: f32,
+}
+
+impl Default for ContactSolverSettings {
+    fn default() -> Self {
+        Self {
+            iterations: 8,
+            correction: 0.8,
+            slop: 0.01,
+            restitution_threshold: 0.5,
+        }
+    }
+}
+
+/// Entities whose motion the solver writes.
+pub type MotionQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        Option<&'static mut Velocity>,
+        Option<&'static mut AngularVelocity>,
+    ),
+>;
+
+#[derive(Debug, Clone, Copy)]
+struct SolverBody {
+    inverse_mass: f32,
+    /// World-space inverse inertia tensor.
+    inverse_inertia: Mat3,
+    center: Vec3,
+    velocity: Vec3,
+    angular_velocity: Vec3,
+    /// Accumulated changes, in world space.
+    delta_velocity: Vec3,
+    delta_angular_velocity: Vec3,
+    delta_position: Vec3,
+}
+
+impl SolverBody {
+    fn point_velocity(&self, point: Vec3) -> Vec3 {
+        self.velocity + self.angular_velocity.cross(point - self.center)
+    }
+
+    fn apply_impulse(&mut self, impulse: Vec3, point: Vec3) {
+        let dv = impulse * self.inverse_mass;
+        let dw = self.inverse_inertia * (point - self.center).cross(impulse);
+        self.velocity += dv;
+        self.angular_velocity += dw;
+        self.delta_velocity += dv;
+        self.delta_angular_velocity += dw;
+    }
+
+    /// Inverse of the effective mass the body opposes to an impulse at `point` along `direction`.
+    fn inverse_effective_mass(&self, point: Vec3, direction: Vec3) -> f32 {
+        let r = point - self.center;
+        self.inverse_mass + direction.dot((self.inverse_inertia * r.cross(direction)).cross(r))
+    }
+}
+
+struct SolverContact {
+    a: Entity,
+    b: Entity,
+    contact: Contact,
+    material: PhysicsMaterial,
+    /// Normal velocity the contact should end up with, from restitution.
+    target_velocity: f32,
+    normal_impulse: f32,
+    friction_impulse: Vec3,
+}
+
+/// Resolves every contact in [Collisions] with sequential impulses: bodies bounce apart according
+/// to their [PhysicsMaterial]'s restitution, slide with Coulomb friction, and are pushed out of
+/// each other to remove penetration. Impulses act at the contact point, so off-center hits spin bodies.
+///
+/// Only bodies with a [Mass] that aren't [OnRails] respond to contacts. Every other body is
+/// kinematic or static, and acts like an infinitely heavy object moving with its (possibly inherited)
+/// [Velocity] and [AngularVelocity], like a rotating station or an orbiting planet.
+/// Contacts between two [Sleeping] bodies are skipped.
+#[allow(clippy::too_many_arguments)]
+pub fn contact_solver_system(
+    settings: Res<ContactSolverSettings>,
+    collisions: Res<Collisions>,
+    parents: Query<&Parent>,
+    bodies: BodyQuery,
+    materials: Query<&PhysicsMaterial>,
+    dynamics: Query<(&Mass, Option<&Inertia>), Without<OnRails>>,
+    sleeping: Query<(), With<Sleeping>>,
+    globals: Query<&GlobalTransform>,
+    mut motions: ParamSet<(FrameQuery, MotionQuery)>,
+) {
+    let material = |collider: Entity, body: Entity| {
+        materials
+            .get(collider)
+            .or_else(|_| materials.get(body))
+            .copied()
+            .unwrap_or_default()
+    };
+
+    let mut solver_bodies: HashMap<Entity, SolverBody> = HashMap::new();
+    let mut contacts = Vec::new();
+
+    for (collider_a, collider_b, contact) in collisions.iter() {
+        let a = rigid_body(collider_a, &parents, &bodies);
+        let b = rigid_body(collider_b, &parents, &bodies);
+        if (!dynamics.contains(a) && !dynamics.contains(b))
+            || (sleeping.contains(a) && sleeping.contains(b))
+        {
+            continue;
+        }
+
+        for body in [a, b] {
+            solver_bodies.entry(body).or_insert_with(|| {
+                let Motion {
+                    velocity,
+                    angular_velocity,
+                    ..
+                } = world_motion(body, &motions.p0());
+                let global = globals.get(body).copied().unwrap_or_default();
+                let (_, rotation, center) = global.to_scale_rotation_translation();
+
+                let (inverse_mass, inverse_inertia) = match dynamics.get(body) {
+                    Ok((mass, inertia)) if mass.0 > 0.0 => {
+                        let inertia = inertia.copied().unwrap_or_default().world(rotation);
+                        let inverse_inertia = if inertia.determinant().is_normal() {
+                            inertia.inverse()
+                        } else {
+                            Mat3::ZERO
+                        };
+                        (1.0 / mass.0, inverse_inertia)
+                    }
+                    _ => (0.0, Mat3::ZERO),
+                };
+
+                SolverBody {
+                    inverse_mass,
+                    inverse_inertia,
+                    center,
+                    velocity,
+                    angular_velocity,
+                    delta_velocity: Vec3::ZERO,
+                    delta_angular_velocity: Vec3::ZERO,
+                    delta_position: Vec3::ZERO,
+                }
+            });
+        }
+
+        let approach = solver_bodies[&b].point_velocity(contact.point)
+            - solver_bodies[&a].point_velocity(contact.point);
+        let normal_velocity = approach.dot(contact.normal);
+        let material = material(collider_a, a).combine(&material(collider_b, b));
+        let target_velocity = if -normal_velocity > settings.restitution_threshold {
+            -material.restitution * normal_velocity
+        } else {
+            0.0
+        };
+
+        contacts.push(SolverContact {
+            a,
+            b,
+            contact: *contact,
+            material,
+            target_velocity,
+            normal_impulse: 0.0,
+            friction_impulse: Vec3::ZERO,
+        });
+    }
+
+    if contacts.is_empty() {
+        return;
+    }
+
+    for _ in 0..settings.iterations {
+        for contact in contacts.iter_mut() {
+            let Some([a, b]) = solver_bodies.get_many_mut([&contact.a, &contact.b]) else {
+                continue;
+            };
+            let (point, normal) = (contact.contact.point, contact.contact.normal);
+
+            // Normal impulse, accumulated over all iterations so it never pulls the bodies together.
+            let relative = b.point_velocity(point) - a.point_velocity(point);
+            let k =
+                a.inverse_effective_mass(point, normal) + b.inverse_effective_mass(point, normal);
+            if k <= 0.0 {
+                continue;
+            }
+
+            let impulse = (contact.target_velocity - relative.dot(normal)) / k;
+            let total = (contact.normal_impulse + impulse).max(0.0);
+            let impulse = total - contact.normal_impulse;
+            contact.normal_impulse = total;
+            a.apply_impulse(-normal * impulse, point);
+            b.apply_impulse(normal * impulse, point);
+
+            // Friction opposes the sliding velocity, bounded by the friction cone.
+            let relative = b.point_velocity(point) - a.point_velocity(point);
+            let sliding = relative - normal * relative.dot(normal);
+            let Some(tangent) = sliding.try_normalize() else {
+                continue;
+            };
+            let k =
+                a.inverse_effective_mass(point, tangent) + b.inverse_effective_mass(point, tangent);
+            if k <= 0.0 {
+                continue;
+            }
+
+            let impulse = -tangent * (sliding.length() / k);
+            let total = (contact.friction_impulse + impulse)
+                .clamp_length_max(contact.material.friction * contact.normal_impulse);
+            let impulse = total - contact.friction_impulse;
+            contact.friction_impulse = total;
+            a.apply_impulse(-impulse, point);
+            b.apply_impulse(impulse, point);
+        }
+    }
+
+    // Push the bodies apart, proportionally to their inverse masses.
+    for contact in contacts.iter() {
+        let Some([a, b]) = solver_bodies.get_many_mut([&contact.a, &contact.b]) else {
+            continue;
+        };
+
+        let total_inverse_mass = a.inverse_mass + b.inverse_mass;
+        let penetration = contact.contact.depth - settings.slop;
+        if total_inverse_mass <= 0.0 || penetration <= 0.0 {
+            continue;
+        }
+
+        let correction =
+            contact.contact.normal * penetration * settings.correction / total_inverse_mass;
+        a.delta_position -= correction * a.inverse_mass;
+        b.delta_position += correction * b.inverse_mass;
+    }
+
+    for (entity, body) in solver_bodies.iter() {
+        if body.inverse_mass <= 0.0 {
+            continue;
+        }
+
+        // Changes are in world space, but the components are relative to the parent.
+        let parent = parents
+            .get(*entity)
+            .ok()
+            .and_then(|parent| globals.get(parent.get()).ok().copied());
+        let to_local = parent
+            .map(|global| global.affine().inverse())
+            .unwrap_or(Affine3A::IDENTITY);
+        let rotation = parent
+            .map(|global| global.to_scale_rotation_translation().1.inverse())
+            .unwrap_or(Quat::IDENTITY);
+
+        let mut motions = motions.p1();
+        let Ok((mut transform, velocity, angular_velocity)) = motions.get_mut(*entity) else {
+            continue;
+        };
+
+        transform.translation += to_local.transform_vector3(body.delta_position);
+        if let Some(mut velocity) = velocity {
+            velocity.0 += to_local.transform_vector3(body.delta_velocity);
+        }
+        if let Some(mut angular_velocity) = angular_velocity {
+            angular_velocity.0 += rotation * body.delta_angular_velocity;
+        }
+    }
+}
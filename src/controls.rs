@@ -1,6 +1,19 @@
 use bevy::prelude::*;
 
-use crate::impulse::{AngularImpulse, Impulse};
+use crate::{
+    impulse::{AngularImpulse, Impulse, ThrustCharacteristics},
+    physics::{AngularVelocity, Inertia, Mass, PhysicsSet, SimulationTime, Velocity},
+    throttle::{boost_system, Boost},
+    trigger::TriggerAppExt,
+    warp::TimeWarp,
+};
+
+/// [TriggerLayers](crate::trigger::TriggerLayers) bit of [PlayerControlled] entities.
+pub const PLAYER_TRIGGER_LAYER: u32 = 1 << 0;
+
+/// Velocity errors below this, in world units or radians per second, are left alone by [FlightAssist]
+/// so it doesn't keep firing thrusters over rounding errors.
+const ASSIST_DEADBAND: f32 = 1e-3;
 
 /// Marks an entity as controlled by the player, meaning [ship_translational_movement_system]
 /// and [ship_rotational_movement_system] will attempt to apply [Impulse] and [AngularImpulse]
@@ -8,25 +21,139 @@ use crate::impulse::{AngularImpulse, Impulse};
 #[derive(Component)]
 pub struct PlayerControlled;
 
+/// How much [FlightAssist] helps flying the ship.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum FlightAssistMode {
+    /// Pure Newtonian flight: the keys fire the thrusters and nothing else does.
+    Off,
+    /// Cancels the ship's [AngularVelocity] whenever no rotation key is held.
+    #[default]
+    RotationDamping,
+    /// Rotation damping, and the thrusters hold [FlightAssist::commanded_velocity].
+    /// `W` and `S` change the commanded velocity instead of firing the engines directly.
+    Full,
+}
+
+impl FlightAssistMode {
+    pub fn next(self) -> Self {
+        match self {
+            FlightAssistMode::Off => FlightAssistMode::RotationDamping,
+            FlightAssistMode::RotationDamping => FlightAssistMode::Full,
+            FlightAssistMode::Full => FlightAssistMode::Off,
+        }
+    }
+}
+
+/// Lets the movement systems fly a [PlayerControlled] ship for the player, within the limits of its
+/// [ThrustCharacteristics]. `V` cycles through the [FlightAssistMode]s. Ships without it fly as if it was off.
+/// Suspended while [TimeWarp] is requested, so holding a velocity against gravity doesn't cancel the warp.
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct FlightAssist {
+    pub mode: FlightAssistMode,
+    /// Velocity held in [FlightAssistMode::Full], relative to the ship's parent like its [Velocity].
+    pub commanded_velocity: Vec3,
+    /// How fast holding `W` or `S` changes the commanded velocity, in world units per second squared.
+    pub commanded_acceleration: f32,
+    /// Time over which velocity errors are corrected, in seconds. Shorter is snappier, as far as the
+    /// thrusters allow.
+    pub response_time: f32,
+}
+
+impl Default for FlightAssist {
+    fn default() -> Self {
+        Self {
+            mode: FlightAssistMode::default(),
+            commanded_velocity: Vec3::ZERO,
+            commanded_acceleration: 2.0,
+            response_time: 0.5,
+        }
+    }
+}
+
 pub struct ControlsPlugin;
 
 impl Plugin for ControlsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedUpdate, ship_translational_movement_system)
-            .add_systems(FixedUpdate, ship_rotational_movement_system);
+        app.add_systems(
+            FixedUpdate,
+            (
+                ship_translational_movement_system,
+                ship_rotational_movement_system,
+            )
+                .in_set(PhysicsSet::Control)
+                // Assists see the thrust limits of a boost from the tick it starts.
+                .after(boost_system),
+        )
+        .add_systems(Update, (flight_assist_toggle_system, boost_key_system))
+        .add_trigger_layer::<PlayerControlled>(PLAYER_TRIGGER_LAYER)
+        .register_type::<FlightAssist>()
+        .register_type::<FlightAssistMode>();
         //.add_system(animate_ship_camera_effects)
         //.add_startup_system(initial_grab_cursor)
         //.init_resource::<ManualEventReader<MouseMotion>>();
     }
 }
 
+/// `V` switches the [FlightAssist] of [PlayerControlled] ships to the next [FlightAssistMode].
+/// Switching to [FlightAssistMode::Full] holds the velocity the ship has at that moment.
+fn flight_assist_toggle_system(
+    keys: Res<Input<KeyCode>>,
+    mut query: Query<(&mut FlightAssist, Option<&Velocity>), With<PlayerControlled>>,
+) {
+    if !keys.just_pressed(KeyCode::V) {
+        return;
+    }
+
+    for (mut assist, velocity) in query.iter_mut() {
+        assist.mode = assist.mode.next();
+        if assist.mode == FlightAssistMode::Full {
+            assist.commanded_velocity = velocity.map(|v| v.0).unwrap_or(Vec3::ZERO);
+        }
+        info!("flight assist set to {:?}", assist.mode);
+    }
+}
+
+/// `LShift` requests the [Boost] of [PlayerControlled] ships.
+fn boost_key_system(
+    keys: Res<Input<KeyCode>>,
+    mut query: Query<&mut Boost, With<PlayerControlled>>,
+) {
+    if !keys.just_pressed(KeyCode::ShiftLeft) {
+        return;
+    }
+
+    for mut boost in query.iter_mut() {
+        boost.requested = true;
+    }
+}
+
 /// Set entities with [PlayerControlled] component's [Impulse] component values based on user input.
 /// Default: `W` and `S` for acceleration impulses.
+///
+/// In [FlightAssistMode::Full], `W` and `S` change the commanded velocity along the ship's nose instead,
+/// `X` commands a full stop, and the impulse is whatever force brings the ship to that velocity.
+#[allow(clippy::type_complexity)]
 fn ship_translational_movement_system(
     keys: Res<Input<KeyCode>>,
-    mut query: Query<(&mut Impulse, &Transform), With<PlayerControlled>>,
+    time: Res<SimulationTime>,
+    warp: Option<Res<TimeWarp>>,
+    mut query: Query<
+        (
+            &mut Impulse,
+            &Transform,
+            Option<&mut FlightAssist>,
+            Option<&Velocity>,
+            Option<&Mass>,
+            Option<&ThrustCharacteristics>,
+            Option<&Boost>,
+        ),
+        With<PlayerControlled>,
+    >,
 ) {
-    for (mut impulse, transform) in query.iter_mut() {
+    let warping = warp.is_some_and(|warp| warp.requested > 1.0);
+
+    for (mut impulse, transform, assist, velocity, mass, thrust, boost) in query.iter_mut() {
         let mut new_impulse = Vec3::ZERO;
         for key in keys.get_pressed() {
             new_impulse += match key {
@@ -36,16 +163,61 @@ fn ship_translational_movement_system(
             };
         }
 
-        *impulse = Impulse(transform.rotation * new_impulse * 10.0);
+        let velocity = velocity.map(|v| v.0).unwrap_or(Vec3::ZERO);
+        let (Some(mut assist), Some(thrust), false) = (assist, thrust, warping) else {
+            *impulse = Impulse(transform.rotation * new_impulse * 10.0);
+            continue;
+        };
+        if assist.mode != FlightAssistMode::Full {
+            *impulse = Impulse(transform.rotation * new_impulse * 10.0);
+            continue;
+        }
+
+        if keys.pressed(KeyCode::X) {
+            assist.commanded_velocity = Vec3::ZERO;
+        }
+        let change = transform.rotation * new_impulse * assist.commanded_acceleration * time.delta;
+        assist.commanded_velocity += change;
+
+        let error = assist.commanded_velocity - velocity;
+        if error.length() < ASSIST_DEADBAND {
+            *impulse = Impulse::default();
+            continue;
+        }
+
+        let mass = mass.map(|m| m.0).unwrap_or(1.0);
+        let force = error / assist.response_time.max(time.delta) * mass;
+        let boost = boost.map(Boost::factor).unwrap_or(1.0);
+        let local = thrust
+            .scaled(boost)
+            .clamp_force(transform.rotation.inverse() * force);
+        *impulse = Impulse(transform.rotation * local);
     }
 }
 
 /// Rotates the [PlayerControlled] ship around its own axis when A or D is pressed.
+/// With [FlightAssistMode::RotationDamping] or [FlightAssistMode::Full], the ship's spin is cancelled
+/// while neither is held.
+#[allow(clippy::type_complexity)]
 fn ship_rotational_movement_system(
     keys: Res<Input<KeyCode>>,
-    mut query: Query<&mut AngularImpulse, With<PlayerControlled>>,
+    time: Res<SimulationTime>,
+    warp: Option<Res<TimeWarp>>,
+    mut query: Query<
+        (
+            &mut AngularImpulse,
+            &Transform,
+            Option<&FlightAssist>,
+            Option<&AngularVelocity>,
+            Option<&Inertia>,
+            Option<&ThrustCharacteristics>,
+        ),
+        With<PlayerControlled>,
+    >,
 ) {
-    for mut impulse in query.iter_mut() {
+    let warping = warp.is_some_and(|warp| warp.requested > 1.0);
+
+    for (mut impulse, transform, assist, angular_velocity, inertia, thrust) in query.iter_mut() {
         let mut new_impulse = Vec3::ZERO;
         for key in keys.get_pressed() {
             new_impulse += match key {
@@ -55,7 +227,27 @@ fn ship_rotational_movement_system(
             };
         }
 
-        *impulse = AngularImpulse(new_impulse * 10.0);
+        let damping = new_impulse == Vec3::ZERO
+            && !warping
+            && assist.is_some_and(|assist| assist.mode != FlightAssistMode::Off);
+        let (true, Some(assist), Some(thrust)) = (damping, assist, thrust) else {
+            *impulse = AngularImpulse(new_impulse * 10.0);
+            continue;
+        };
+
+        let spin = angular_velocity.map(|v| v.0).unwrap_or(Vec3::ZERO);
+        if spin.length() < ASSIST_DEADBAND {
+            *impulse = AngularImpulse::default();
+            continue;
+        }
+
+        let inertia = inertia
+            .copied()
+            .unwrap_or_default()
+            .world(transform.rotation);
+        let torque = inertia * -spin / assist.response_time.max(time.delta);
+        let local = thrust.clamp_torque(transform.rotation.inverse() * torque);
+        *impulse = AngularImpulse(transform.rotation * local);
     }
 }
 
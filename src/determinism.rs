@@ -0,0 +1,137 @@
+use bevy::{
+    ecs::schedule::{LogLevel, ScheduleBuildSettings},
+    prelude::*,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::physics::{AngularVelocity, PhysicsSet, SimulationTime, Velocity};
+
+/// Source of randomness for anything which affects the simulation. Simulation code must never use
+/// `rand::thread_rng`, or the same inputs would no longer produce the same trajectories. That includes
+/// spawners scattering ships or debris at random: they draw from the tick they spawn in.
+///
+/// Generators are derived from the seed, the tick and a caller-chosen stream, rather than kept
+/// as running state. Resimulating a tick after a [rollback](crate::snapshot::rollback) therefore
+/// draws exactly the same numbers again.
+#[derive(Debug, Clone, Resource, Reflect)]
+pub struct SimulationRng {
+    pub seed: u64,
+}
+
+impl SimulationRng {
+    /// Generator for the given tick. Use a different `stream` for each independent consumer,
+    /// for example the entity's bits, so consumers don't depend on the order systems run in.
+    pub fn rng(&self, tick: u64, stream: u64) -> StdRng {
+        let mut hash = Fnv1a::default();
+        hash.write(self.seed);
+        hash.write(tick);
+        hash.write(stream);
+        StdRng::seed_from_u64(hash.0)
+    }
+}
+
+/// Checksum of the physics state at the end of the latest tick. Two runs fed the same inputs
+/// must produce the same sequence of checksums, which is what tests and replays compare.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Resource, Reflect)]
+pub struct PhysicsChecksum {
+    pub tick: u64,
+    pub value: u64,
+}
+
+/// 64-bit FNV-1a, which unlike the standard library's hasher is specified and stable across Rust versions.
+#[derive(Debug, Clone, Copy)]
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv1a {
+    fn write(&mut self, value: u64) {
+        for byte in value.to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_floats(&mut self, values: &[f32]) {
+        for value in values {
+            self.write(value.to_bits() as u64);
+        }
+    }
+}
+
+/// Makes the simulation reproducible and verifiable: provides the seeded [SimulationRng] and
+/// computes a [PhysicsChecksum] every tick. Physics systems already advance by the fixed
+/// [SimulationTime::delta] and are ordered through [PhysicsSet].
+#[derive(Default)]
+pub struct DeterminismPlugin {
+    pub seed: u64,
+    /// Logs a warning for every pair of `FixedUpdate` systems which access the same data in
+    /// an unspecified order. Meant for development, when adding new simulation systems.
+    pub report_ambiguities: bool,
+}
+
+impl Plugin for DeterminismPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SimulationRng { seed: self.seed })
+            .init_resource::<PhysicsChecksum>()
+            .add_systems(
+                FixedUpdate,
+                physics_checksum_system.after(PhysicsSet::Solve),
+            )
+            .register_type::<SimulationRng>()
+            .register_type::<PhysicsChecksum>();
+
+        if self.report_ambiguities {
+            app.edit_schedule(FixedUpdate, |schedule| {
+                schedule.set_build_settings(ScheduleBuildSettings {
+                    ambiguity_detection: LogLevel::Warn,
+                    ..default()
+                });
+            });
+        }
+    }
+}
+
+/// Hashes the exact bit patterns of the [Transform], [Velocity] and [AngularVelocity] of every
+/// simulated entity, in entity order, into the [PhysicsChecksum].
+#[allow(clippy::type_complexity)]
+pub fn physics_checksum_system(
+    time: Res<SimulationTime>,
+    mut checksum: ResMut<PhysicsChecksum>,
+    bodies: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&Velocity>,
+            Option<&AngularVelocity>,
+        ),
+        Or<(With<Velocity>, With<AngularVelocity>)>,
+    >,
+) {
+    let mut bodies: Vec<_> = bodies.iter().collect();
+    bodies.sort_unstable_by_key(|(entity, ..)| *entity);
+
+    let mut hash = Fnv1a::default();
+    hash.write(time.tick);
+    for (entity, transform, velocity, angular_velocity) in bodies {
+        hash.write(entity.to_bits());
+        hash.write_floats(&transform.translation.to_array());
+        hash.write_floats(&transform.rotation.to_array());
+        hash.write_floats(&velocity.map(|v| v.0).unwrap_or(Vec3::ZERO).to_array());
+        hash.write_floats(
+            &angular_velocity
+                .map(|v| v.0)
+                .unwrap_or(Vec3::ZERO)
+                .to_array(),
+        );
+    }
+
+    *checksum = PhysicsChecksum {
+        tick: time.tick,
+        value: hash.0,
+    };
+}
@@ -3,13 +3,35 @@ use bevy::{
     prelude::*,
 };
 use bevy_hanabi::{
-    Attribute, ColorOverLifetimeModifier, EffectAsset, ExprWriter, Gradient, HanabiPlugin,
-    ImageSampleMapping, OrientMode, OrientModifier, ParticleEffect, ParticleEffectBundle,
-    ParticleTextureModifier, SetAttributeModifier, SetPositionSphereModifier, ShapeDimension,
-    SizeOverLifetimeModifier, Spawner,
+    Attribute, ColorOverLifetimeModifier, CompiledParticleEffect, EffectAsset, ExprWriter,
+    Gradient, HanabiPlugin, ImageSampleMapping, OrientMode, OrientModifier, ParticleEffect,
+    ParticleEffectBundle, ParticleTextureModifier, SetAttributeModifier, SetPositionSphereModifier,
+    ShapeDimension, SizeOverLifetimeModifier, Spawner,
 };
 
-use crate::{controls::PlayerControlled, GameState};
+use crate::{
+    controls::PlayerControlled,
+    origin::{recenter_origin_system, OriginShifted},
+    GameState,
+};
+
+/// Property of a particle effect which [shift_particles_system] sets to the distance its live
+/// particles have to move by this frame, see [ShiftedByOrigin].
+pub const ORIGIN_SHIFT_PROPERTY: &str = "origin_shift";
+
+/// Marks particle effects simulated in global space, whose live particles have to move along with
+/// the root [Transform]s when the render origin shifts. Their effect needs an [ORIGIN_SHIFT_PROPERTY]
+/// property and the [origin_shift_modifier].
+#[derive(Component)]
+pub struct ShiftedByOrigin;
+
+/// Update modifier moving an effect's particles by its [ORIGIN_SHIFT_PROPERTY].
+pub fn origin_shift_modifier(writer: &ExprWriter) -> SetAttributeModifier {
+    let shifted = writer
+        .attr(Attribute::POSITION)
+        .add(writer.prop(ORIGIN_SHIFT_PROPERTY));
+    SetAttributeModifier::new(Attribute::POSITION, shifted.expr())
+}
 
 pub struct DustPlugin;
 
@@ -17,7 +39,29 @@ impl Plugin for DustPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_plugins(HanabiPlugin)
             .add_systems(OnEnter(GameState::Running), create_space_dust)
-            .add_systems(FixedUpdate, parent_dust_emitter_to_camera);
+            .add_systems(FixedUpdate, parent_dust_emitter_to_camera)
+            .add_systems(
+                PostUpdate,
+                shift_particles_system.after(recenter_origin_system),
+            );
+    }
+}
+
+/// Moves the live particles of every effect [ShiftedByOrigin] along with the render origin for one
+/// frame, and leaves them alone afterwards.
+fn shift_particles_system(
+    mut events: EventReader<OriginShifted>,
+    mut shifting: Local<bool>,
+    mut effects: Query<&mut CompiledParticleEffect, With<ShiftedByOrigin>>,
+) {
+    let shift = -events.read().map(|event| event.offset).sum::<Vec3>();
+    if shift == Vec3::ZERO && !*shifting {
+        return;
+    }
+    *shifting = shift != Vec3::ZERO;
+
+    for mut effect in effects.iter_mut() {
+        effect.set_property(ORIGIN_SHIFT_PROPERTY, shift.into());
     }
 }
 
@@ -64,12 +108,16 @@ fn create_space_dust(
     let init_velocity =
         SetAttributeModifier::new(Attribute::VELOCITY, writer.lit(Vec3::ZERO).expr());
 
+    let origin_shift = origin_shift_modifier(&writer);
+
     let effect = effects.add(
         EffectAsset::new(160000, Spawner::rate(2000.0.into()), writer.finish())
             .with_name("emit:dust")
+            .with_property(ORIGIN_SHIFT_PROPERTY, Vec3::ZERO.into())
             .init(init_position)
             .init(init_velocity)
             .init(init_lifetime)
+            .update(origin_shift)
             .render(color_lifetime)
             .render(size_lifetime)
             .render(OrientModifier {
@@ -83,6 +131,7 @@ fn create_space_dust(
 
     commands.spawn((
         DustEmitter,
+        ShiftedByOrigin,
         ParticleEffectBundle {
             transform: Transform::from_xyz(0.0, -10.0, 0.0),
             effect: ParticleEffect::new(effect.clone()),
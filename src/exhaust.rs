@@ -25,7 +25,10 @@ use bevy_hanabi::{
 use log::debug;
 
 use crate::{
+    dust::{origin_shift_modifier, ShiftedByOrigin, ORIGIN_SHIFT_PROPERTY},
+    heat::ThermalStatus,
     physics::{Acceleration, Velocity},
+    throttle::Boost,
     GameState,
 };
 
@@ -95,6 +98,8 @@ fn create_exhaust_effect(
     let exhaust_velocity = writer.prop("exhaust_velocity").expr();
     let init_velocity = SetAttributeModifier::new(Attribute::VELOCITY, exhaust_velocity);
 
+    let origin_shift = origin_shift_modifier(&writer);
+
     let effect = EffectAsset::new(
         100,
         Spawner::rate(50.0.into()).with_starts_active(false),
@@ -102,9 +107,11 @@ fn create_exhaust_effect(
     )
     .with_name("emit:exhaust")
     .with_property("exhaust_velocity", Vec3::ZERO.into())
+    .with_property(ORIGIN_SHIFT_PROPERTY, Vec3::ZERO.into())
     .init(init_position)
     .init(init_velocity)
     .init(init_lifetime)
+    .update(origin_shift)
     .render(color_lifetime)
     .render(size_lifetime)
     .render(OrientModifier {
@@ -160,6 +167,7 @@ fn associate_exhaust_effect_with_thruster(
                         ExhaustVelocity {
                             parent_entity: ship,
                         },
+                        ShiftedByOrigin,
                     ))
                     .id();
 
@@ -171,7 +179,13 @@ fn associate_exhaust_effect_with_thruster(
 
 fn update_exhaust_velocity(
     time: Res<Time>,
-    simulated_entities: Query<(&GlobalTransform, &Velocity, &Acceleration)>,
+    simulated_entities: Query<(
+        &GlobalTransform,
+        &Velocity,
+        &Acceleration,
+        Option<&Boost>,
+        Option<&ThermalStatus>,
+    )>,
     mut query: Query<(
         &mut CompiledParticleEffect,
         &mut EffectSpawner,
@@ -179,11 +193,20 @@ fn update_exhaust_velocity(
     )>,
 ) {
     for (mut compiled, mut spawner, exhaust) in query.iter_mut() {
-        if let Ok((transform, velocity, acceleration)) =
+        if let Ok((transform, velocity, acceleration, boost, thermal)) =
             simulated_entities.get(exhaust.parent_entity)
         {
-            if acceleration.length_squared() > 0.1 {
-                let velocity = -transform.forward() * 0.5
+            // An overheating engine sputters, cutting out more often the hotter it runs.
+            let sputtering = thermal
+                .is_some_and(|t| (time.elapsed_seconds_wrapped() * 15.0).fract() > t.thrust_limit);
+
+            if acceleration.length_squared() > 0.1 && !sputtering {
+                // A boosting engine blasts its exhaust out further.
+                let blast = match boost {
+                    Some(boost) if boost.is_active() => boost.multiplier,
+                    _ => 1.0,
+                };
+                let velocity = -transform.forward() * 0.5 * blast
                     + velocity.0 * 0.5
                     + transform.right() * (time.elapsed_seconds_wrapped() % 0.01 - 0.005) * 20.0;
                 compiled.set_property("exhaust_velocity", velocity.into());
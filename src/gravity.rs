@@ -0,0 +1,361 @@
+use bevy::prelude::*;
+
+use crate::{
+    physics::{force_resolve_system, Acceleration, Force, PhysicsSet},
+    sleep::Sleeping,
+    snapshot::ReflectSnapshot,
+    soi::SoiTracked,
+};
+
+/// Marks an entity as a source of gravity. `mu` is the standard gravitational parameter
+/// (`G * M`) of the body in world units³/s², so the acceleration it imparts at distance `r`
+/// is `mu / r²`.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+pub struct GravitySource {
+    pub mu: f32,
+}
+
+/// Gravitational acceleration an entity felt during the latest tick, in world units per second squared.
+/// Recorded by [gravity_system] and [primary_gravity_system](crate::soi::primary_gravity_system) for
+/// every attracted entity, including [Sleeping] ones, whose [Acceleration] it isn't added to.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct FeltGravity(pub Vec3);
+
+/// Records `gravity` in the [FeltGravity] of `entity`, and adds it to its `acceleration` unless it's [Sleeping].
+pub fn apply_gravity(
+    commands: &mut Commands,
+    entity: Entity,
+    gravity: Vec3,
+    sleeping: bool,
+    acceleration: &mut Acceleration,
+    felt: Option<Mut<FeltGravity>>,
+) {
+    if !sleeping {
+        acceleration.0 += gravity;
+    }
+    match felt {
+        Some(mut felt) => felt.0 = gravity,
+        None => {
+            commands.entity(entity).insert(FeltGravity(gravity));
+        }
+    }
+}
+
+/// Tunables for the [gravity_system].
+#[derive(Debug, Clone, Resource, Reflect)]
+pub struct GravitySettings {
+    /// Plummer softening length. Keeps the acceleration finite when an entity passes through
+    /// (or very close to) the center of a source.
+    pub softening: f32,
+    /// Number of sources above which the field is approximated with a Barnes-Hut octree
+    /// instead of summing every source directly.
+    pub barnes_hut_threshold: usize,
+    /// Barnes-Hut opening angle. A node of size `s` at distance `d` is treated as a single
+    /// source when `s / d < theta`. Smaller values are more accurate, but slower.
+    pub theta: f32,
+}
+
+impl Default for GravitySettings {
+    fn default() -> Self {
+        Self {
+            softening: 0.1,
+            barnes_hut_threshold: 256,
+            theta: 0.5,
+        }
+    }
+}
+
+pub struct GravityPlugin;
+
+impl Plugin for GravityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GravitySettings>()
+            .add_systems(
+                FixedUpdate,
+                gravity_system
+                    .in_set(PhysicsSet::Resolve)
+                    .after(force_resolve_system),
+            )
+            .register_type::<GravitySettings>()
+            .register_type::<GravitySource>()
+            .register_type::<FeltGravity>();
+    }
+}
+
+/// A single point mass contributing to a [GravityField].
+#[derive(Debug, Clone, Copy)]
+pub struct PointSource {
+    pub entity: Entity,
+    pub position: Vec3,
+    pub mu: f32,
+}
+
+/// Snapshot of every [GravitySource] in the world, which can be sampled at arbitrary positions.
+/// Small fields are summed directly, large ones through a Barnes-Hut [Octree].
+pub struct GravityField {
+    sources: Vec<PointSource>,
+    octree: Option<Octree>,
+    softening_squared: f32,
+    theta: f32,
+}
+
+impl GravityField {
+    pub fn new(sources: Vec<PointSource>, settings: &GravitySettings) -> Self {
+        let octree = (sources.len() > settings.barnes_hut_threshold).then(|| Octree::new(&sources));
+
+        Self {
+            sources,
+            octree,
+            softening_squared: settings.softening * settings.softening,
+            theta: settings.theta,
+        }
+    }
+
+    /// Gravitational acceleration at `position`. Sources belonging to `exclude` are ignored,
+    /// so bodies which are themselves a source don't attract themselves.
+    pub fn acceleration_at(&self, position: Vec3, exclude: Option<Entity>) -> Vec3 {
+        match &self.octree {
+            Some(octree) => octree.acceleration_at(
+                &self.sources,
+                position,
+                exclude,
+                self.theta,
+                self.softening_squared,
+            ),
+            None => self
+                .sources
+                .iter()
+                .filter(|source| Some(source.entity) != exclude)
+                .map(|source| {
+                    point_acceleration(source.position, source.mu, position, self.softening_squared)
+                })
+                .sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+}
+
+/// Softened (Plummer) acceleration towards a point mass at `source`.
+pub(crate) fn point_acceleration(
+    source: Vec3,
+    mu: f32,
+    position: Vec3,
+    softening_squared: f32,
+) -> Vec3 {
+    let offset = source - position;
+    let distance_squared = offset.length_squared() + softening_squared;
+    if distance_squared.is_normal() {
+        offset * (mu / (distance_squared * distance_squared.sqrt()))
+    } else {
+        Vec3::ZERO
+    }
+}
+
+/// Maximum number of sources kept in a leaf before it is subdivided.
+const OCTREE_LEAF_CAPACITY: usize = 8;
+/// Stops subdividing past this depth, in case many sources share (nearly) the same position.
+const OCTREE_MAX_DEPTH: u32 = 16;
+
+struct OctreeNode {
+    center: Vec3,
+    half_size: f32,
+    /// `mu`-weighted center of all sources below this node.
+    center_of_mass: Vec3,
+    mu: f32,
+    /// Index of the first of eight consecutive child nodes, if the node has been subdivided.
+    children: Option<usize>,
+    /// Indices into the source list, only populated for leaves.
+    sources: Vec<usize>,
+}
+
+/// Barnes-Hut octree over a set of [PointSource]s, stored as a flat list of nodes.
+pub struct Octree {
+    nodes: Vec<OctreeNode>,
+}
+
+impl Octree {
+    pub fn new(sources: &[PointSource]) -> Self {
+        let (min, max) = sources.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), source| (min.min(source.position), max.max(source.position)),
+        );
+        let center = (min + max) * 0.5;
+        let half_size = ((max - min).max_element() * 0.5).max(f32::EPSILON);
+
+        let mut octree = Octree {
+            nodes: vec![OctreeNode {
+                center,
+                half_size,
+                center_of_mass: Vec3::ZERO,
+                mu: 0.0,
+                children: None,
+                sources: (0..sources.len()).collect(),
+            }],
+        };
+        octree.subdivide(0, sources, 0);
+        octree
+    }
+
+    fn subdivide(&mut self, node: usize, sources: &[PointSource], depth: u32) {
+        let indices = std::mem::take(&mut self.nodes[node].sources);
+
+        let mu: f32 = indices.iter().map(|&i| sources[i].mu).sum();
+        let center_of_mass = if mu.abs() > 0.0 {
+            indices
+                .iter()
+                .map(|&i| sources[i].position * sources[i].mu)
+                .sum::<Vec3>()
+                / mu
+        } else {
+            self.nodes[node].center
+        };
+        self.nodes[node].mu = mu;
+        self.nodes[node].center_of_mass = center_of_mass;
+
+        if indices.len() <= OCTREE_LEAF_CAPACITY || depth >= OCTREE_MAX_DEPTH {
+            self.nodes[node].sources = indices;
+            return;
+        }
+
+        let center = self.nodes[node].center;
+        let half_size = self.nodes[node].half_size * 0.5;
+        let first_child = self.nodes.len();
+
+        let mut buckets: [Vec<usize>; 8] = Default::default();
+        for i in indices {
+            buckets[octant(center, sources[i].position)].push(i);
+        }
+
+        for (octant, bucket) in buckets.into_iter().enumerate() {
+            let direction = Vec3::new(
+                if octant & 1 != 0 { 1.0 } else { -1.0 },
+                if octant & 2 != 0 { 1.0 } else { -1.0 },
+                if octant & 4 != 0 { 1.0 } else { -1.0 },
+            );
+            self.nodes.push(OctreeNode {
+                center: center + direction * half_size,
+                half_size,
+                center_of_mass: Vec3::ZERO,
+                mu: 0.0,
+                children: None,
+                sources: bucket,
+            });
+        }
+        self.nodes[node].children = Some(first_child);
+
+        for child in first_child..first_child + 8 {
+            self.subdivide(child, sources, depth + 1);
+        }
+    }
+
+    fn acceleration_at(
+        &self,
+        sources: &[PointSource],
+        position: Vec3,
+        exclude: Option<Entity>,
+        theta: f32,
+        softening_squared: f32,
+    ) -> Vec3 {
+        let mut acceleration = Vec3::ZERO;
+        let mut stack = vec![0];
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if node.mu == 0.0 {
+                continue;
+            }
+
+            let Some(first_child) = node.children else {
+                acceleration += node
+                    .sources
+                    .iter()
+                    .map(|&i| &sources[i])
+                    .filter(|source| Some(source.entity) != exclude)
+                    .map(|source| {
+                        point_acceleration(source.position, source.mu, position, softening_squared)
+                    })
+                    .sum::<Vec3>();
+                continue;
+            };
+
+            // Never approximate a node containing the sample position,
+            // since it might contain the excluded source itself.
+            let outside =
+                ((position - node.center).abs() - Vec3::splat(node.half_size)).max_element() > 0.0;
+            let distance = node.center_of_mass.distance(position);
+
+            if outside && node.half_size * 2.0 < theta * distance {
+                acceleration +=
+                    point_acceleration(node.center_of_mass, node.mu, position, softening_squared);
+            } else {
+                stack.extend(first_child..first_child + 8);
+            }
+        }
+
+        acceleration
+    }
+}
+
+fn octant(center: Vec3, position: Vec3) -> usize {
+    (position.x >= center.x) as usize
+        | ((position.y >= center.y) as usize) << 1
+        | ((position.z >= center.z) as usize) << 2
+}
+
+/// Collects every [GravitySource] into a [GravityField] and adds the resulting gravitational
+/// acceleration to every force-driven entity's [Acceleration]. Runs after [force_resolve_system],
+/// which rebuilds the [Acceleration] of those entities every tick, so gravity never accumulates.
+/// [SoiTracked] entities only feel their primary, see [primary_gravity_system](crate::soi::primary_gravity_system).
+/// [Sleeping] entities only get their [FeltGravity] updated, since their [Acceleration] isn't rebuilt.
+///
+/// Entities with an [Acceleration] but no [Force] are deliberately not attracted: nothing resets their
+/// [Acceleration] between ticks, so gravity would pile up on it. Give them a [Force] to make them fall.
+#[allow(clippy::type_complexity)]
+pub fn gravity_system(
+    mut commands: Commands,
+    settings: Res<GravitySettings>,
+    sources: Query<(Entity, &GlobalTransform, &GravitySource)>,
+    mut attracted: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut Acceleration,
+            Has<Sleeping>,
+            Option<&mut FeltGravity>,
+        ),
+        (With<Force>, Without<SoiTracked>),
+    >,
+) {
+    let field = GravityField::new(
+        sources
+            .iter()
+            .map(|(entity, transform, source)| PointSource {
+                entity,
+                position: transform.translation(),
+                mu: source.mu,
+            })
+            .collect(),
+        &settings,
+    );
+
+    if field.is_empty() {
+        return;
+    }
+
+    for (entity, transform, mut acceleration, sleeping, felt) in attracted.iter_mut() {
+        let gravity = field.acceleration_at(transform.translation(), Some(entity));
+        apply_gravity(
+            &mut commands,
+            entity,
+            gravity,
+            sleeping,
+            &mut acceleration,
+            felt,
+        );
+    }
+}
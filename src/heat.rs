@@ -0,0 +1,319 @@
+use std::f32::consts::PI;
+
+use bevy::{math::Affine3A, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    allocation::ThrustAllocation,
+    atmosphere::{atmospheric_drag_system, AtmosphericConditions},
+    collision::Collider,
+    impulse::virtual_engine_force,
+    local_system::{BodyKind, CelestialBody},
+    physics::{PhysicsSet, SimulationTime},
+    propellant::propellant_system,
+    snapshot::ReflectSnapshot,
+    thruster::{thruster_force_system, Thruster, Thrusters},
+};
+
+/// Temperature radiators cool a ship down to, in kelvin. Life support and avionics keep it at least this warm.
+pub const REST_TEMPERATURE: f32 = 290.0;
+
+/// Temperature of a ship, in kelvin.
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct Temperature(pub f32);
+
+impl Default for Temperature {
+    fn default() -> Self {
+        Self(REST_TEMPERATURE)
+    }
+}
+
+/// Heat needed to warm a ship by one kelvin, in joules per kelvin.
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+pub struct HeatCapacity(pub f32);
+
+impl Default for HeatCapacity {
+    fn default() -> Self {
+        Self(20.0)
+    }
+}
+
+/// Heat flowing into a ship from sources other than its engines and the atmosphere, such as weapons,
+/// in watts. Accumulated like a [Force](crate::physics::Force) during [PhysicsSet::Forces], and
+/// cleared by [heat_system] once it's been applied.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+pub struct HeatFlux(pub f32);
+
+/// Waste heat of a ship's engines, in watts per newton of thrust.
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+pub struct EngineHeat(pub f32);
+
+impl Default for EngineHeat {
+    fn default() -> Self {
+        Self(20.0)
+    }
+}
+
+/// Sheds a ship's heat into space. The output grows with the ship's surface, estimated from the
+/// sphere around its [Collider], and with how far the ship is above [REST_TEMPERATURE].
+#[derive(Debug, Clone, Copy, Deserialize, Component, Reflect)]
+pub struct Radiators {
+    /// Heat shed per square world unit of surface and kelvin above [REST_TEMPERATURE], in watts.
+    pub conductance: f32,
+    /// Fraction of their output the radiators keep in direct sunlight. In the shadow of a planet,
+    /// or far from any star, they work at full output.
+    pub sunlit_efficiency: f32,
+}
+
+impl Default for Radiators {
+    fn default() -> Self {
+        Self {
+            conductance: 0.15,
+            sunlit_efficiency: 0.5,
+        }
+    }
+}
+
+/// Temperatures, in kelvin, at which a ship starts to suffer from heat.
+#[derive(Debug, Clone, Copy, Deserialize, Component, Reflect)]
+pub struct HeatTolerance {
+    /// Above this, the engines are throttled back to protect them, down to [HeatTolerance::min_thrust]
+    /// at [HeatTolerance::critical].
+    pub overheat: f32,
+    /// Above this, the hull takes damage.
+    pub critical: f32,
+    /// [HullIntegrity] lost per second and kelvin above [HeatTolerance::critical].
+    pub damage_rate: f32,
+    /// Fraction of the engines' thrust left at and above [HeatTolerance::critical]. Engines running
+    /// flat out there still heat the ship, so pilots who keep pushing damage their hull.
+    pub min_thrust: f32,
+}
+
+impl Default for HeatTolerance {
+    fn default() -> Self {
+        Self {
+            overheat: 400.0,
+            critical: 500.0,
+            damage_rate: 1e-3,
+            min_thrust: 0.25,
+        }
+    }
+}
+
+/// Structural health of a ship's hull, `1.0` when intact and `0.0` when wrecked.
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct HullIntegrity(pub f32);
+
+impl Default for HullIntegrity {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Summary of a ship's heat for the HUD and for AI pilots, updated every tick by [heat_system].
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct ThermalStatus {
+    /// `0.0` at [REST_TEMPERATURE] and `1.0` at [HeatTolerance::overheat]. Pilots who want to keep
+    /// their full thrust hold it below `1.0`.
+    pub heat_level: f32,
+    /// Fraction of the engines' thrust available at the current temperature, applied by the
+    /// [impulse_system](crate::impulse::impulse_system).
+    pub thrust_limit: f32,
+    /// Net heat flowing into the ship during the latest tick, in watts. Negative while cooling down.
+    pub net_heat: f32,
+    /// Whether a star shines on the ship, hampering its radiators.
+    pub sunlit: bool,
+}
+
+impl Default for ThermalStatus {
+    fn default() -> Self {
+        Self {
+            heat_level: 0.0,
+            thrust_limit: 1.0,
+            net_heat: 0.0,
+            sunlit: false,
+        }
+    }
+}
+
+impl ThermalStatus {
+    pub fn is_overheating(&self) -> bool {
+        self.thrust_limit < 1.0
+    }
+}
+
+/// Components a ship needs to heat up and cool down.
+#[derive(Default, Bundle)]
+pub struct HeatBundle {
+    pub temperature: Temperature,
+    pub capacity: HeatCapacity,
+    pub flux: HeatFlux,
+    pub engine_heat: EngineHeat,
+    pub radiators: Radiators,
+    pub tolerance: HeatTolerance,
+    pub hull: HullIntegrity,
+    pub status: ThermalStatus,
+}
+
+pub struct HeatPlugin;
+
+impl Plugin for HeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            heat_system
+                .after(thruster_force_system)
+                .after(propellant_system)
+                .after(atmospheric_drag_system)
+                .in_set(PhysicsSet::Forces),
+        )
+        .register_type::<HeatCapacity>()
+        .register_type::<HeatFlux>()
+        .register_type::<EngineHeat>()
+        .register_type::<Radiators>()
+        .register_type::<HeatTolerance>()
+        .register_type::<Temperature>()
+        .register_type::<HullIntegrity>()
+        .register_type::<ThermalStatus>();
+    }
+}
+
+/// Heats every ship with a [Temperature] by the thrust its engines produced this tick, its entry
+/// heating from [AtmosphericConditions] and its [HeatFlux], and cools it with its [Radiators].
+/// Then updates its [ThermalStatus], and damages its [HullIntegrity] while it's above the critical
+/// temperature of its [HeatTolerance].
+///
+/// Engine thrust is the [Thruster::output] of the ship's [Thrusters], plus the magnitude along each
+/// axis of the [virtual_engine_force] of its other engines.
+#[allow(clippy::type_complexity)]
+pub fn heat_system(
+    time: Res<SimulationTime>,
+    celestials: Query<(&CelestialBody, &GlobalTransform)>,
+    mut ships: Query<(
+        &mut Temperature,
+        &HeatCapacity,
+        &GlobalTransform,
+        Option<&mut HeatFlux>,
+        Option<&EngineHeat>,
+        Option<&Radiators>,
+        Option<&HeatTolerance>,
+        Option<&mut HullIntegrity>,
+        Option<&mut ThermalStatus>,
+        Option<&Thrusters>,
+        Option<&ThrustAllocation>,
+        Option<&AtmosphericConditions>,
+        Option<&Collider>,
+    )>,
+    thrusters: Query<&Thruster>,
+) {
+    let bodies: Vec<(BodyKind, Vec3, f32)> = celestials
+        .iter()
+        .map(|(body, transform)| (body.kind, transform.translation(), body.radius))
+        .collect();
+
+    for (
+        mut temperature,
+        capacity,
+        transform,
+        flux,
+        engine_heat,
+        radiators,
+        tolerance,
+        hull,
+        status,
+        owned,
+        allocation,
+        conditions,
+        collider,
+    ) in ships.iter_mut()
+    {
+        let owned_thrusters: Vec<&Thruster> = owned
+            .iter()
+            .flat_map(|owned| owned.0.iter())
+            .filter_map(|entity| thrusters.get(*entity).ok())
+            .collect();
+        let thrust = owned_thrusters.iter().map(|t| t.output()).sum::<f32>()
+            + virtual_engine_force(allocation, owned_thrusters)
+                .abs()
+                .dot(Vec3::ONE);
+
+        let mut heating = engine_heat.map(|e| e.0 * thrust).unwrap_or_default()
+            + conditions.map(|c| c.heating).unwrap_or_default();
+        if let Some(mut flux) = flux {
+            heating += flux.0;
+            flux.0 = 0.0;
+        }
+
+        let position = transform.translation();
+        let sunlit = in_sunlight(position, &bodies);
+        let cooling = radiators
+            .map(|radiators| {
+                let radius = collider
+                    .map(|c| c.aabb(&Affine3A::IDENTITY))
+                    .map(|aabb| (aabb.max - aabb.min).length() * 0.5)
+                    .unwrap_or(1.0);
+                let efficiency = if sunlit {
+                    radiators.sunlit_efficiency
+                } else {
+                    1.0
+                };
+                radiators.conductance
+                    * 4.0
+                    * PI
+                    * radius
+                    * radius
+                    * efficiency
+                    * (temperature.0 - REST_TEMPERATURE).max(0.0)
+            })
+            .unwrap_or_default();
+
+        // Radiators can cool the ship down to its resting temperature, but never below, even during long ticks.
+        let net_heat = heating - cooling;
+        temperature.0 = (temperature.0 + net_heat * time.delta / capacity.0.max(f32::EPSILON))
+            .max(REST_TEMPERATURE.min(temperature.0));
+
+        let tolerance = tolerance.copied().unwrap_or_default();
+        if let Some(mut hull) = hull {
+            let excess = temperature.0 - tolerance.critical;
+            if excess > 0.0 {
+                hull.0 = (hull.0 - tolerance.damage_rate * excess * time.delta).max(0.0);
+            }
+        }
+
+        if let Some(mut status) = status {
+            // A tolerance with coinciding thresholds jumps from one to the other instead of dividing by zero.
+            *status = ThermalStatus {
+                heat_level: (temperature.0 - REST_TEMPERATURE)
+                    / (tolerance.overheat - REST_TEMPERATURE).max(f32::EPSILON),
+                thrust_limit: 1.0
+                    - (1.0 - tolerance.min_thrust)
+                        * ((temperature.0 - tolerance.overheat)
+                            / (tolerance.critical - tolerance.overheat).max(f32::EPSILON))
+                        .clamp(0.0, 1.0),
+                net_heat,
+                sunlit,
+            };
+        }
+    }
+}
+
+/// Whether a line from `position` to any of the stars among `bodies` misses every planet.
+fn in_sunlight(position: Vec3, bodies: &[(BodyKind, Vec3, f32)]) -> bool {
+    bodies
+        .iter()
+        .filter(|(kind, ..)| *kind == BodyKind::Star)
+        .any(|(_, star, _)| {
+            let ray = *star - position;
+            !bodies
+                .iter()
+                .filter(|(kind, ..)| *kind == BodyKind::Planet)
+                .any(|(_, center, radius)| {
+                    let along =
+                        ((*center - position).dot(ray) / ray.length_squared()).clamp(0.0, 1.0);
+                    (position + ray * along).distance_squared(*center) < radius * radius
+                })
+        })
+}
@@ -1,24 +1,50 @@
 use bevy::math::Vec3;
 use bevy::prelude::*;
 
-use crate::physics::{Acceleration, AngularAcceleration, PhysicsBundle};
+use crate::{
+    allocation::{allocate, Actuator, ThrustAllocation},
+    heat::ThermalStatus,
+    physics::{Force, PhysicsBundle, PhysicsSet, SimulationTime, Torque},
+    propellant::{has_propellant, PropellantTank},
+    snapshot::ReflectSnapshot,
+    throttle::{Boost, EngineThrottles, SpoolCurve},
+    thruster::{Thruster, Thrusters},
+};
 
-/// Specifies the Angular impulse imparted on the object via the [angular_impulse_system] into [AngularAcceleration].
+/// Weight of torque errors relative to force errors when allocating thrust: missing the requested
+/// torque by 1 newton-metre counts as much as missing the requested force by 1 newton.
+const TORQUE_WEIGHT: f32 = 1.0;
+
+/// Local directions of the virtual engines of [ThrustCharacteristics::translation_actuators], in order.
+pub const TRANSLATION_AXES: [Vec3; 6] = [
+    Vec3::X,
+    Vec3::NEG_X,
+    Vec3::Y,
+    Vec3::NEG_Y,
+    Vec3::Z,
+    Vec3::NEG_Z,
+];
+
+/// Specifies the impulse imparted on the object via the [impulse_system] into [Force], in newtons.
 /// **NOTE:** The impulse is relative to the entity's local position, not the entity's position in the world.
 /// This means that applying an impulse of say [0.0, 0.0, 1.0] will always make the entity move along its local "forward"-axis relative to itself, rather than along the global Z-axis
-#[derive(Debug, Default, Component, Reflect)]
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
 pub struct Impulse(pub Vec3);
 
-/// Specifies the impulse imparted on the object via the [impulse_system] into [Acceleration].
+/// Specifies the angular impulse imparted on the object via the [impulse_system] into [Torque], in newton-metres.
 /// **NOTE:** The impulse is relative to the entity's local rotation, not the entity's rotation in the world.
 /// This means that applying an angular impulse of say [0.0, 1.0, 0.0] will always make the entity rotate along its local yaw-axis relative to itself, rather than along the global Y-axis
-#[derive(Debug, Default, Component, Reflect)]
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
 pub struct AngularImpulse(pub Vec3);
 
-/// This cobbled-together structure was/is intended to define the maximum acceleration of the ship in any direction.
+/// This cobbled-together structure was/is intended to define the maximum thrust of the ship in any direction,
+/// in newtons (`rot` in newton-metres). The resulting acceleration depends on the ship's [Mass](crate::physics::Mass)
+/// and [Inertia](crate::physics::Inertia).
 /// For example, it might make sense to define an instance of this structure that defines a ship which can accelerate very
 /// fast in the forward direction, but relatively slowly along the other axis to simulate a larger rear engine compared to smaller RCS-thrusters for instance.
-/// The structure is used by the [impulse_system] and [angular_impulse_system]s to limit the impact of an Impulse.
+/// The structure is used by the [impulse_system] to limit the impact of an Impulse.
 #[derive(Debug, Component, Reflect)]
 pub struct ThrustCharacteristics {
     pub min: Vec3,
@@ -37,6 +63,56 @@ impl Default for ThrustCharacteristics {
     }
 }
 
+impl ThrustCharacteristics {
+    /// One virtual engine along each of the [TRANSLATION_AXES], for the directions a ship has no [Thrusters] for.
+    pub fn translation_actuators(&self) -> [Actuator; 6] {
+        [
+            Actuator::at(Vec3::ZERO, Vec3::X * self.max.x),
+            Actuator::at(Vec3::ZERO, Vec3::X * self.min.x),
+            Actuator::at(Vec3::ZERO, Vec3::Y * self.max.y),
+            Actuator::at(Vec3::ZERO, Vec3::Y * self.min.y),
+            Actuator::at(Vec3::ZERO, Vec3::Z * self.max.z),
+            Actuator::at(Vec3::ZERO, Vec3::Z * self.min.z),
+        ]
+    }
+
+    /// Thrust the ship can produce along a unit `direction` given relative to the ship, in newtons.
+    pub fn available(&self, direction: Vec3) -> f32 {
+        (direction.max(Vec3::ZERO) * self.max + direction.min(Vec3::ZERO) * self.min).length()
+    }
+
+    /// These limits with the thrust along every axis multiplied by `factor`, like during a [Boost].
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            min: self.min * factor,
+            max: self.max * factor,
+            rot: self.rot,
+        }
+    }
+
+    /// Limits a force given relative to the ship to what it can produce along each local axis.
+    pub fn clamp_force(&self, force: Vec3) -> Vec3 {
+        force.clamp(self.min, self.max)
+    }
+
+    /// Limits a torque given relative to the ship to what it can produce around each local axis.
+    pub fn clamp_torque(&self, torque: Vec3) -> Vec3 {
+        torque.clamp(-self.rot, self.rot)
+    }
+
+    /// Reaction wheels turning the ship around each local axis in both directions.
+    pub fn rotation_actuators(&self) -> [Actuator; 6] {
+        [
+            Actuator::torque(Vec3::X * self.rot.x),
+            Actuator::torque(-Vec3::X * self.rot.x),
+            Actuator::torque(Vec3::Y * self.rot.y),
+            Actuator::torque(-Vec3::Y * self.rot.y),
+            Actuator::torque(Vec3::Z * self.rot.z),
+            Actuator::torque(-Vec3::Z * self.rot.z),
+        ]
+    }
+}
+
 /// [Bundle](https://erasin.wang/books/bevy-cheatbook/programming/ec.html#component-bundles) containing common Ship components.
 /// [PhysicsBundle](crate::physics::PhysicsBundle) + Ship control components
 #[derive(Default, Bundle)]
@@ -44,6 +120,8 @@ pub struct ShipBundle {
     pub impulse: Impulse,
     pub angular_impulse: AngularImpulse,
     pub thrust_characteristics: ThrustCharacteristics,
+    pub allocation: ThrustAllocation,
+    pub throttles: EngineThrottles,
     pub physics: PhysicsBundle,
     pub spatial: SpatialBundle,
 }
@@ -52,73 +130,196 @@ pub struct ImpulsePlugin;
 
 impl Plugin for ImpulsePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedUpdate, impulse_system)
-            .add_systems(FixedUpdate, angular_impulse_system)
+        app.add_systems(FixedUpdate, impulse_system.in_set(PhysicsSet::Forces))
             .register_type::<ThrustCharacteristics>()
+            .register_type::<ThrustAllocation>()
             .register_type::<Impulse>()
             .register_type::<AngularImpulse>();
     }
 }
 
-/// Takes an entity's [Impulse] component and imparts it on the entity
-/// while respecting the entity's [ThrustCharacteristics]
+/// Distributes an entity's [Impulse] and [AngularImpulse] over its actuators with [allocate],
+/// and records the outcome in its [ThrustAllocation].
+///
+/// Ships with [Thrusters] translate with those: their throttles are set here and applied by
+/// [thruster_force_system](crate::thruster::thruster_force_system), so off-center or damaged thrusters
+/// also produce torque. Every direction along a local axis which no thruster points along gets a
+/// virtual engine instead, sized by the ship's [ThrustCharacteristics], so ships without thrusters
+/// get one along each local axis. [ThrustCharacteristics::rot] adds reaction wheels for turning.
+/// Engines whose [PropellantTank] ran dry are left out, as if their thrust limits were zero, a
+/// boosting ship's engines are stronger by its [Boost::factor], and an overheating ship only gets
+/// the [ThermalStatus::thrust_limit] of its engines' thrust.
+///
+/// Engines with a [SpoolCurve] don't reach the allocated throttle at once: their output follows it
+/// along the curve, and the [ThrustAllocation::achieved_force] is what they actually deliver.
+/// Reaction wheels respond instantly.
+#[allow(clippy::type_complexity)]
 pub fn impulse_system(
-    mut query: Query<(
-        &mut Acceleration,
+    time: Res<SimulationTime>,
+    mut vessels: Query<(
+        Entity,
+        &mut Force,
+        &mut Torque,
+        Option<&mut ThrustAllocation>,
         &Impulse,
+        &AngularImpulse,
         &Transform,
         &ThrustCharacteristics,
+        Option<&Thrusters>,
+        Option<&mut EngineThrottles>,
+        Option<&ThermalStatus>,
+        Option<&Boost>,
     )>,
+    mut thrusters: Query<&mut Thruster>,
+    tanks: Query<&PropellantTank>,
+    curves: Query<&SpoolCurve>,
 ) {
-    for (mut acceleration, impulse, transform, thrust) in query.iter_mut() {
-        acceleration.0 = if impulse.0.length_squared().is_normal() {
-            // Thrust characteristics are defined relative to the ship body,
-            // so we need to apply the inverse rotation to the set impulse
-            // before we compare it to the Thrust Characteristics, otherwise
-            // they will be defining world space constraints.
-            let translated_impulse = transform.rotation.inverse() * impulse.0;
-
-            let l = (thrust.max / translated_impulse).abs();
-            let h = (thrust.min / translated_impulse).abs();
-
-            let smallest_factor = [l.x, l.y, l.z, h.x, h.y, h.z, translated_impulse.length()]
-                .iter()
-                .cloned()
-                .filter(|f| f.is_normal())
-                .reduce(f32::min)
-                .unwrap_or(0.0);
-
-            impulse.0 * smallest_factor
-        } else {
-            Vec3::ZERO
-        };
-    }
-}
+    for (
+        vessel,
+        mut force,
+        mut torque,
+        allocation,
+        impulse,
+        angular_impulse,
+        transform,
+        thrust,
+        owned,
+        engine_throttles,
+        thermal,
+        boost,
+    ) in vessels.iter_mut()
+    {
+        // Impulses are given in world space, but actuators are described relative to the ship.
+        let requested_force = transform.rotation.inverse() * impulse.0;
+        let requested_torque = transform.rotation.inverse() * angular_impulse.0;
 
-/// Takes an entity's [AngularImpulse] component and imparts it on the entity relative to the entity's current rotation
-/// while respecting the entity's [ThrustCharacteristics]
-pub fn angular_impulse_system(
-    mut query: Query<(
-        &mut AngularAcceleration,
-        &AngularImpulse,
-        &ThrustCharacteristics,
-    )>,
-) {
-    for (mut acceleration, impulse, thrust) in query.iter_mut() {
-        acceleration.0 = if impulse.0.length_squared().is_normal() {
-            let l = (thrust.rot / impulse.0).abs();
-            let h = ((-thrust.rot) / impulse.0).abs();
-
-            let smallest_factor = [l.x, l.y, l.z, h.x, h.y, h.z, impulse.0.length()]
-                .iter()
-                .cloned()
-                .filter(|f| f.is_normal())
-                .reduce(f32::min)
-                .unwrap_or(0.0);
-
-            impulse.0 * smallest_factor
-        } else {
-            Vec3::ZERO
+        let boost = boost.map(Boost::factor).unwrap_or(1.0);
+        let mut actuators = Vec::new();
+        let mut thruster_entities = Vec::new();
+        let mut covered = Vec::new();
+        for entity in owned.iter().flat_map(|owned| owned.0.iter()) {
+            let Ok(mut thruster) = thrusters.get_mut(*entity) else {
+                continue;
+            };
+            covered.push(thruster.direction);
+            thruster.boost = boost;
+            if !has_propellant(Some(*entity), vessel, &tanks) {
+                thruster.throttle = 0.0;
+                thruster.level = 0.0;
+                continue;
+            }
+            actuators.push(Actuator::at(
+                thruster.position,
+                thruster.direction * thruster.max_force * boost * thruster.condition,
+            ));
+            thruster_entities.push(*entity);
+        }
+
+        // Virtual engines push along every axis no thruster points along, in the slots of [EngineThrottles].
+        let mut virtual_slots = Vec::new();
+        if has_propellant(None, vessel, &tanks) {
+            for (slot, actuator) in thrust.translation_actuators().into_iter().enumerate() {
+                let axis = TRANSLATION_AXES[slot];
+                if covered.iter().any(|direction| direction.dot(axis) > 0.5) {
+                    continue;
+                }
+                actuators.push(Actuator {
+                    force: actuator.force * boost,
+                    torque: actuator.torque * boost,
+                });
+                virtual_slots.push(slot);
+            }
+        }
+        let engines = actuators.len();
+        actuators.extend(thrust.rotation_actuators());
+
+        // An overheating ship allocates as if its engines were weaker, and throttles them back to match.
+        let thrust_limit = thermal.map(|t| t.thrust_limit).unwrap_or(1.0);
+        let limited: Vec<Actuator> = actuators
+            .iter()
+            .enumerate()
+            .map(|(i, actuator)| {
+                if i < engines {
+                    Actuator {
+                        force: actuator.force * thrust_limit,
+                        torque: actuator.torque * thrust_limit,
+                    }
+                } else {
+                    *actuator
+                }
+            })
+            .collect();
+
+        let result = allocate(&limited, requested_force, requested_torque, TORQUE_WEIGHT);
+
+        // What every actuator delivers this tick, after spooling towards its allocated throttle.
+        let vessel_curve = curves.get(vessel).ok();
+        let mut levels = result.throttles.clone();
+        for level in levels[..engines].iter_mut() {
+            *level *= thrust_limit;
+        }
+        for (entity, level) in thruster_entities.iter().zip(levels.iter_mut()) {
+            if let Ok(mut thruster) = thrusters.get_mut(*entity) {
+                thruster.throttle = *level;
+                thruster.level = match curves.get(*entity).ok().or(vessel_curve) {
+                    Some(curve) => curve.step(thruster.level, thruster.throttle, time.delta),
+                    None => thruster.throttle,
+                };
+                *level = thruster.level;
+            }
+        }
+        if let Some(mut engine_throttles) = engine_throttles {
+            let mut current = [0.0; 6];
+            let virtual_levels = &mut levels[thruster_entities.len()..engines];
+            for (slot, level) in virtual_slots.iter().zip(virtual_levels) {
+                if let Some(curve) = vessel_curve {
+                    *level = curve.step(engine_throttles.0[*slot], *level, time.delta);
+                }
+                current[*slot] = *level;
+            }
+            engine_throttles.0 = current;
+        }
+
+        // Everything which isn't a thruster entity pushes the ship directly.
+        let (direct_force, direct_torque) = actuators
+            .iter()
+            .zip(levels.iter())
+            .skip(thruster_entities.len())
+            .fold((Vec3::ZERO, Vec3::ZERO), |(f, t), (actuator, level)| {
+                (f + actuator.force * *level, t + actuator.torque * *level)
+            });
+        force.0 += transform.rotation * direct_force;
+        torque.0 += transform.rotation * direct_torque;
+
+        if let Some(mut allocation) = allocation {
+            let (achieved_force, achieved_torque) = actuators.iter().zip(levels.iter()).fold(
+                (Vec3::ZERO, Vec3::ZERO),
+                |(f, t), (actuator, level)| {
+                    (f + actuator.force * *level, t + actuator.torque * *level)
+                },
+            );
+            *allocation = ThrustAllocation {
+                requested_force,
+                requested_torque,
+                achieved_force,
+                achieved_torque,
+            };
         }
     }
 }
+
+/// Force the virtual engines of a ship delivered during the latest tick, relative to the ship: whatever
+/// its [ThrustAllocation] achieved beyond the output of its `thrusters`.
+pub fn virtual_engine_force<'a>(
+    allocation: Option<&ThrustAllocation>,
+    thrusters: impl IntoIterator<Item = &'a Thruster>,
+) -> Vec3 {
+    let Some(allocation) = allocation else {
+        return Vec3::ZERO;
+    };
+    thrusters
+        .into_iter()
+        .fold(allocation.achieved_force, |force, thruster| {
+            force - thruster.direction * thruster.output()
+        })
+}
@@ -0,0 +1,177 @@
+use bevy::{
+    prelude::*,
+    time::Fixed,
+    transform::{
+        systems::{propagate_transforms, sync_simple_transforms},
+        TransformSystem,
+    },
+};
+
+use crate::{
+    origin::{recenter_origin_system, OriginShifted},
+    physics::{AngularVelocity, PhysicsSet, Velocity},
+    snapshot::ReflectSnapshot,
+};
+
+/// Physics state of an entity moved in `FixedUpdate`, kept apart from its [Transform] so the
+/// [Transform] can be rendered in between two physics ticks.
+///
+/// During `FixedUpdate` the entity's [Transform] is authoritative as usual. Once per frame,
+/// [interpolate_transform_system] stores it in [InterpolatedTransform::current] and replaces it with
+/// a blend of [InterpolatedTransform::previous] and [InterpolatedTransform::current], weighted by how
+/// far the frame is into the next tick. [restore_transform_system] puts the authoritative value
+/// back before the next frame simulates anything.
+///
+/// Added automatically to every entity with a [Velocity] or [AngularVelocity], unless it has
+/// [NoInterpolation]. Other entities moved in `FixedUpdate`, such as cameras, can add it themselves.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct InterpolatedTransform {
+    /// [Transform] at the start of the latest physics tick.
+    pub previous: Transform,
+    /// [Transform] at the end of the latest physics tick.
+    pub current: Transform,
+    /// [Transform] written for rendering this frame. If the entity's [Transform] no longer matches it
+    /// when the next frame starts, something outside of `FixedUpdate` moved the entity.
+    pub rendered: Transform,
+}
+
+impl InterpolatedTransform {
+    /// State of an entity which has been resting at `transform`.
+    pub fn new(transform: Transform) -> Self {
+        Self {
+            previous: transform,
+            current: transform,
+            rendered: transform,
+        }
+    }
+
+    /// Blend between [InterpolatedTransform::previous] (at `0.0`) and [InterpolatedTransform::current] (at `1.0`).
+    pub fn at(&self, fraction: f32) -> Transform {
+        Transform {
+            translation: self
+                .previous
+                .translation
+                .lerp(self.current.translation, fraction),
+            rotation: self
+                .previous
+                .rotation
+                .slerp(self.current.rotation, fraction),
+            scale: self.previous.scale.lerp(self.current.scale, fraction),
+        }
+    }
+}
+
+/// Opts an entity out of render interpolation: its [Transform] is rendered exactly as the latest
+/// physics tick left it. Meant for entities which must snap, like ones teleported every tick.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+pub struct NoInterpolation;
+
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            (
+                restore_transform_system,
+                // Physics reads GlobalTransforms, which still hold the rendered values otherwise.
+                sync_simple_transforms,
+                propagate_transforms,
+            )
+                .chain(),
+        )
+        .add_systems(FixedUpdate, start_tick_system.before(PhysicsSet::Control))
+        .add_systems(Update, insert_interpolated_transform_system)
+        .add_systems(
+            PostUpdate,
+            // Re-centering works on the authoritative Transforms, before they are interpolated.
+            (
+                shift_interpolated_transform_system.after(recenter_origin_system),
+                interpolate_transform_system,
+            )
+                .chain()
+                .before(TransformSystem::TransformPropagate),
+        )
+        .register_type::<NoInterpolation>()
+        // A rollback restores the tick's start as well, so the interpolation carries on seamlessly.
+        .register_type::<InterpolatedTransform>();
+    }
+}
+
+/// Gives every newly simulated entity an [InterpolatedTransform], unless it opted out with [NoInterpolation].
+#[allow(clippy::type_complexity)]
+pub fn insert_interpolated_transform_system(
+    mut commands: Commands,
+    bodies: Query<
+        (Entity, &Transform),
+        (
+            Or<(Added<Velocity>, Added<AngularVelocity>)>,
+            Without<InterpolatedTransform>,
+            Without<NoInterpolation>,
+        ),
+    >,
+) {
+    for (entity, transform) in bodies.iter() {
+        commands
+            .entity(entity)
+            .insert(InterpolatedTransform::new(*transform));
+    }
+}
+
+/// Replaces the rendered [Transform] with the authoritative one from [InterpolatedTransform::current].
+///
+/// Entities whose [Transform] was changed after it was rendered, for example by being teleported,
+/// keep the new value and are not interpolated towards it.
+pub fn restore_transform_system(
+    mut bodies: Query<(&mut Transform, &mut InterpolatedTransform), Without<NoInterpolation>>,
+) {
+    for (mut transform, mut interpolated) in bodies.iter_mut() {
+        if *transform == interpolated.rendered {
+            *transform = interpolated.current;
+        } else {
+            *interpolated = InterpolatedTransform::new(*transform);
+        }
+    }
+}
+
+/// Remembers the [Transform] every interpolated entity starts the physics tick with.
+pub fn start_tick_system(
+    mut bodies: Query<(&Transform, &mut InterpolatedTransform), Without<NoInterpolation>>,
+) {
+    for (transform, mut interpolated) in bodies.iter_mut() {
+        interpolated.previous = *transform;
+    }
+}
+
+/// Stores the authoritative [Transform] of every interpolated entity, and renders it at the
+/// fraction of a tick which has passed since the latest physics tick.
+pub fn interpolate_transform_system(
+    fixed: Res<Time<Fixed>>,
+    mut bodies: Query<(&mut Transform, &mut InterpolatedTransform), Without<NoInterpolation>>,
+) {
+    let fraction = fixed.overstep_percentage().clamp(0.0, 1.0);
+    for (mut transform, mut interpolated) in bodies.iter_mut() {
+        if interpolated.is_added() {
+            interpolated.previous = *transform;
+        }
+
+        interpolated.current = *transform;
+        interpolated.rendered = interpolated.at(fraction);
+        *transform = interpolated.rendered;
+    }
+}
+
+/// Moves the start of the latest tick of root entities along with their [Transform] when the render origin
+/// shifts. [interpolate_transform_system] picks up the shifted [Transform] itself.
+pub fn shift_interpolated_transform_system(
+    mut events: EventReader<OriginShifted>,
+    mut roots: Query<&mut InterpolatedTransform, Without<Parent>>,
+) {
+    for event in events.read() {
+        for mut interpolated in roots.iter_mut() {
+            interpolated.previous.translation -= event.offset;
+        }
+    }
+}
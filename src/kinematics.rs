@@ -0,0 +1,176 @@
+use bevy::{
+    ecs::{query::ReadOnlyWorldQuery, system::EntityCommands, world::EntityWorldMut},
+    prelude::*,
+};
+
+use crate::physics::{AngularVelocity, Velocity};
+
+/// Position, orientation and motion of an entity relative to some reference frame, usually its parent's.
+/// [Velocity] and [AngularVelocity] are stored like this, so a body parked on a spinning station
+/// has no local velocity but still moves through the world.
+///
+/// Unlike [GlobalTransform], the world-space [Motion] computed by [world_motion] is up to date within
+/// the physics tick. Frames are assumed not to be scaled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    pub transform: Transform,
+    pub velocity: Vec3,
+    pub angular_velocity: Vec3,
+}
+
+impl Motion {
+    /// A frame at rest at the origin, which is what entities without a parent are relative to.
+    pub const IDENTITY: Self = Self {
+        transform: Transform::IDENTITY,
+        velocity: Vec3::ZERO,
+        angular_velocity: Vec3::ZERO,
+    };
+
+    /// Expresses `local`, given relative to this frame, relative to the frame this one is given in.
+    /// Points fixed in a rotating frame pick up the tangential velocity `ω × r`.
+    pub fn transform_motion(&self, local: &Motion) -> Motion {
+        let transform = self.transform.mul_transform(local.transform);
+        let offset = transform.translation - self.transform.translation;
+
+        Motion {
+            transform,
+            velocity: self.velocity
+                + self.angular_velocity.cross(offset)
+                + self.transform.rotation * local.velocity,
+            angular_velocity: self.angular_velocity
+                + self.transform.rotation * local.angular_velocity,
+        }
+    }
+
+    /// Inverse of [Motion::transform_motion]: expresses this motion relative to `frame`,
+    /// where both are given relative to the same outer frame.
+    pub fn relative_to(&self, frame: &Motion) -> Motion {
+        let inverse = frame.transform.rotation.inverse();
+        let offset = self.transform.translation - frame.transform.translation;
+
+        Motion {
+            transform: Transform::from_matrix(
+                frame.transform.compute_matrix().inverse() * self.transform.compute_matrix(),
+            ),
+            velocity: inverse
+                * (self.velocity - frame.velocity - frame.angular_velocity.cross(offset)),
+            angular_velocity: inverse * (self.angular_velocity - frame.angular_velocity),
+        }
+    }
+}
+
+/// Entities whose local motion [world_motion] walks through. `F` can exclude entities a system
+/// is mutating, as long as those aren't reference frames themselves.
+pub type FrameQuery<'w, 's, F = ()> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        Option<&'static Velocity>,
+        Option<&'static AngularVelocity>,
+        Option<&'static Parent>,
+    ),
+    F,
+>;
+
+/// World-space [Motion] of `entity`, computed from the [Transform]s, [Velocity]s and
+/// [AngularVelocity]s of it and its ancestors.
+pub fn world_motion<F: ReadOnlyWorldQuery>(entity: Entity, frames: &FrameQuery<F>) -> Motion {
+    world_motion_with(entity, &|entity| {
+        let (transform, velocity, angular_velocity, parent) = frames.get(entity).ok()?;
+        Some((
+            Motion {
+                transform: *transform,
+                velocity: velocity.map(|v| v.0).unwrap_or(Vec3::ZERO),
+                angular_velocity: angular_velocity.map(|v| v.0).unwrap_or(Vec3::ZERO),
+            },
+            parent.map(|p| p.get()),
+        ))
+    })
+}
+
+fn world_motion_with(
+    entity: Entity,
+    local: &impl Fn(Entity) -> Option<(Motion, Option<Entity>)>,
+) -> Motion {
+    let Some((motion, parent)) = local(entity) else {
+        return Motion::IDENTITY;
+    };
+
+    match parent {
+        Some(parent) => world_motion_with(parent, local).transform_motion(&motion),
+        None => motion,
+    }
+}
+
+/// Re-parents `entity`, converting its [Transform], [Velocity] and [AngularVelocity] into the new
+/// parent's frame so its motion through the world is unchanged.
+fn reparent_in_motion(world: &mut World, entity: Entity, parent: Option<Entity>) {
+    let motion_of = |world: &World, entity: Entity| {
+        world_motion_with(entity, &|entity| {
+            Some((
+                Motion {
+                    transform: *world.get::<Transform>(entity)?,
+                    velocity: world
+                        .get::<Velocity>(entity)
+                        .map(|v| v.0)
+                        .unwrap_or(Vec3::ZERO),
+                    angular_velocity: world
+                        .get::<AngularVelocity>(entity)
+                        .map(|v| v.0)
+                        .unwrap_or(Vec3::ZERO),
+                },
+                world.get::<Parent>(entity).map(|p| p.get()),
+            ))
+        })
+    };
+
+    let motion = motion_of(world, entity);
+    let frame = parent
+        .map(|parent| motion_of(world, parent))
+        .unwrap_or(Motion::IDENTITY);
+    let local = motion.relative_to(&frame);
+
+    let Some(mut entity) = world.get_entity_mut(entity) else {
+        return;
+    };
+    match parent {
+        Some(parent) => entity.set_parent(parent),
+        None => entity.remove_parent(),
+    };
+
+    if let Some(mut transform) = entity.get_mut::<Transform>() {
+        *transform = local.transform;
+    }
+    if let Some(mut velocity) = entity.get_mut::<Velocity>() {
+        velocity.0 = local.velocity;
+    }
+    if let Some(mut angular_velocity) = entity.get_mut::<AngularVelocity>() {
+        angular_velocity.0 = local.angular_velocity;
+    }
+}
+
+pub trait ReparentInMotionExt {
+    /// Like `set_parent`, but keeps the entity's world-space position and velocity, for example when docking.
+    fn set_parent_in_motion(&mut self, parent: Entity) -> &mut Self;
+
+    /// Like `remove_parent`, but keeps the entity's world-space position and velocity, for example when
+    /// undocking from a spinning station.
+    fn remove_parent_in_motion(&mut self) -> &mut Self;
+}
+
+impl ReparentInMotionExt for EntityCommands<'_, '_, '_> {
+    fn set_parent_in_motion(&mut self, parent: Entity) -> &mut Self {
+        self.add(move |entity: EntityWorldMut| {
+            let id = entity.id();
+            reparent_in_motion(entity.into_world_mut(), id, Some(parent));
+        })
+    }
+
+    fn remove_parent_in_motion(&mut self) -> &mut Self {
+        self.add(|entity: EntityWorldMut| {
+            let id = entity.id();
+            reparent_in_motion(entity.into_world_mut(), id, None);
+        })
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::{prelude::*, reflect::TypePath};
+use bevy_common_assets::ron::RonAssetPlugin;
+use serde::Deserialize;
+
+use crate::{
+    atmosphere::Atmosphere,
+    collision::Collider,
+    gravity::GravitySource,
+    orbit::{OrbitBundle, OrbitalElements},
+};
+
+/// Orbital periods in the `*.system.ron` files are given in years.
+pub const SECONDS_PER_YEAR: f64 = 365.25 * 24.0 * 60.0 * 60.0;
+
+/// Bodies which don't specify their gravitational parameter get one proportional to their volume.
+/// The constant is chosen so the Sun in `real.system.ron` gets its real `mu` in units of 10⁶km.
+const MU_PER_CUBIC_SIZE: f32 = 3.95e-7;
+
+/// A star system as described by a `*.system.ron` asset.
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+pub struct LocalSystem {
+    pub name: String,
+    pub position: Vec3,
+    pub center: Body,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Reflect)]
+pub enum BodyKind {
+    Star,
+    Planet,
+}
+
+/// A celestial body and everything orbiting it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Body {
+    pub name: String,
+    pub kind: BodyKind,
+    /// Radius of the body in world units.
+    pub size: f32,
+    /// Standard gravitational parameter of the body. Derived from [Body::size] if omitted.
+    #[serde(default)]
+    pub mu: Option<f32>,
+    #[serde(default)]
+    pub atmosphere: Option<Atmosphere>,
+    #[serde(default)]
+    pub bodies: Vec<Orbit>,
+}
+
+impl Body {
+    pub fn mu(&self) -> f32 {
+        self.mu
+            .unwrap_or_else(|| MU_PER_CUBIC_SIZE * self.size.powi(3))
+    }
+}
+
+/// A [Body] orbiting its parent. Only the period is required; the orbit is circular and
+/// in the parent's XZ-plane unless specified otherwise. Angles are in degrees.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Orbit {
+    pub body: Body,
+    /// Orbital period in years.
+    pub period: f64,
+    /// Derived from the period and the parent's `mu` through Kepler's third law if omitted.
+    #[serde(default)]
+    pub semi_major_axis: Option<f32>,
+    #[serde(default)]
+    pub eccentricity: f32,
+    #[serde(default)]
+    pub inclination: f32,
+    #[serde(default)]
+    pub longitude_of_ascending_node: f32,
+    #[serde(default)]
+    pub argument_of_periapsis: f32,
+    #[serde(default)]
+    pub mean_anomaly: f32,
+}
+
+impl Orbit {
+    pub fn elements(&self, parent_mu: f32) -> OrbitalElements {
+        let period = self.period * SECONDS_PER_YEAR;
+
+        OrbitalElements {
+            semi_major_axis: self
+                .semi_major_axis
+                .unwrap_or_else(|| OrbitalElements::semi_major_axis_around(parent_mu, period)),
+            eccentricity: self.eccentricity,
+            inclination: self.inclination.to_radians(),
+            longitude_of_ascending_node: self.longitude_of_ascending_node.to_radians(),
+            argument_of_periapsis: self.argument_of_periapsis.to_radians(),
+            mean_anomaly_at_epoch: (self.mean_anomaly as f64).to_radians(),
+            mean_motion: std::f64::consts::TAU / period,
+        }
+    }
+}
+
+/// Attached to every entity spawned from a [Body].
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct CelestialBody {
+    pub kind: BodyKind,
+    pub radius: f32,
+}
+
+pub struct LocalSystemPlugin;
+
+impl Plugin for LocalSystemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<LocalSystem>::new(&["system.ron"]))
+            .add_systems(Update, spawn_local_system)
+            .register_type::<CelestialBody>();
+    }
+}
+
+/// Builds the bodies of a [LocalSystem] as children of every entity holding a `Handle<LocalSystem>`,
+/// once the asset has finished loading.
+fn spawn_local_system(
+    mut commands: Commands,
+    systems: Res<Assets<LocalSystem>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    roots: Query<(Entity, &Handle<LocalSystem>), Without<Children>>,
+) {
+    for (root, handle) in roots.iter() {
+        let Some(system) = systems.get(handle) else {
+            continue;
+        };
+
+        info!("spawning local system '{}'", system.name);
+        commands.entity(root).with_children(|parent| {
+            spawn_body(
+                parent,
+                &system.center,
+                None,
+                Transform::from_translation(system.position),
+                &mut meshes,
+                &mut materials,
+            );
+        });
+    }
+}
+
+/// Spawns `body` and, recursively, everything orbiting it. `orbit` is the body's orbit
+/// together with the gravitational parameter of the body it orbits, if any.
+fn spawn_body(
+    parent: &mut ChildBuilder,
+    body: &Body,
+    orbit: Option<(&Orbit, f32)>,
+    transform: Transform,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+) {
+    let material = match body.kind {
+        BodyKind::Star => StandardMaterial {
+            base_color: Color::WHITE,
+            emissive: Color::WHITE,
+            ..default()
+        },
+        BodyKind::Planet => StandardMaterial {
+            base_color: Color::rgb(0.5, 0.5, 0.6),
+            reflectance: 0.0,
+            ..default()
+        },
+    };
+
+    let mu = body.mu();
+    let mut entity = parent.spawn((
+        Name::new(body.name.clone()),
+        CelestialBody {
+            kind: body.kind,
+            radius: body.size,
+        },
+        GravitySource { mu },
+        Collider::Sphere { radius: body.size },
+        SpatialBundle::from_transform(transform),
+    ));
+
+    if let Some(atmosphere) = body.atmosphere {
+        entity.insert(atmosphere);
+    }
+
+    if let Some((orbit, parent_mu)) = orbit {
+        entity.insert(OrbitBundle {
+            elements: orbit.elements(parent_mu),
+            ..Default::default()
+        });
+    }
+
+    entity.with_children(|frame| {
+        // The mesh is a child so it can be spun or scaled without affecting orbiting children.
+        frame.spawn(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::UVSphere {
+                radius: body.size,
+                sectors: 32,
+                stacks: 16,
+            })),
+            material: materials.add(material),
+            ..default()
+        });
+
+        if body.kind == BodyKind::Star {
+            frame.spawn(PointLightBundle {
+                point_light: PointLight {
+                    intensity: 50000.0,
+                    range: 1000.0,
+                    ..default()
+                },
+                ..default()
+            });
+        }
+
+        for orbit in body.bodies.iter() {
+            spawn_body(
+                frame,
+                &orbit.body,
+                Some((orbit, mu)),
+                Transform::default(),
+                meshes,
+                materials,
+            );
+        }
+    });
+}
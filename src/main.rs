@@ -7,24 +7,67 @@ use bevy_egui::{
 };
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
+use atmosphere::AtmospherePlugin;
 use bevy_kira_audio::AudioPlugin;
 use camera::TrackingCameraPlugin;
-use controls::ControlsPlugin;
+use collision::CollisionPlugin;
+use controls::{ControlsPlugin, PlayerControlled};
+use determinism::DeterminismPlugin;
 use dust::DustPlugin;
 use exhaust::ExhaustPlugin;
+use gravity::GravityPlugin;
+use heat::{HeatPlugin, HullIntegrity, ThermalStatus};
 use impulse::ImpulsePlugin;
+use interpolation::InterpolationPlugin;
+use local_system::LocalSystemPlugin;
+use orbit::OrbitPlugin;
+use origin::FloatingOriginPlugin;
 use physics::PhysicsPlugin;
+use propellant::{DeltaV, PropellantPlugin, PropellantTank};
+use reentry::ReentryGlowPlugin;
+use ship_class::ShipClassPlugin;
+use sleep::SleepPlugin;
+use snapshot::SnapshotPlugin;
+use soi::SoiPlugin;
+use throttle::ThrottlePlugin;
 use thrust::ThrustPlugin;
+use thruster::ThrusterPlugin;
+use trajectory::TrajectoryPlugin;
+use trigger::TriggerPlugin;
+use warp::{TimeWarp, TimeWarpPlugin};
 
+mod allocation;
+mod atmosphere;
 mod camera;
+mod collision;
 mod controls;
+mod determinism;
 mod dust;
 mod exhaust;
+mod gravity;
+mod heat;
 mod impulse;
+mod interpolation;
+mod kinematics;
+mod local_system;
+mod orbit;
+mod origin;
 mod physics;
+mod propellant;
+mod reentry;
+mod ship_class;
+mod sleep;
+mod snapshot;
+mod soi;
+mod station;
 mod tests;
+mod throttle;
 mod thrust;
+mod thruster;
 mod tracking;
+mod trajectory;
+mod trigger;
+mod warp;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, States, Default, ScheduleLabel)]
 enum GameState {
@@ -60,21 +103,48 @@ fn main() {
         .add_plugins(WorldInspectorPlugin::new())
         .add_plugins(AudioPlugin)
         .add_plugins(PhysicsPlugin)
+        .add_plugins(DeterminismPlugin::default())
+        .add_plugins(TimeWarpPlugin)
+        .add_plugins(GravityPlugin)
+        .add_plugins(AtmospherePlugin)
+        .add_plugins(OrbitPlugin)
+        .add_plugins(LocalSystemPlugin)
+        .add_plugins(SoiPlugin)
+        .add_plugins(CollisionPlugin)
+        .add_plugins(SleepPlugin)
+        .add_plugins(TriggerPlugin)
+        .add_plugins(SnapshotPlugin)
+        .add_plugins(FloatingOriginPlugin)
+        .add_plugins(InterpolationPlugin)
         .add_plugins(ThrustPlugin)
+        .add_plugins(ThrusterPlugin)
+        .add_plugins(TrajectoryPlugin)
         .add_plugins(ControlsPlugin)
         .add_plugins(ImpulsePlugin)
+        .add_plugins(PropellantPlugin)
+        .add_plugins(ThrottlePlugin)
+        .add_plugins(HeatPlugin)
+        .add_plugins(ShipClassPlugin)
         .add_plugins(DustPlugin)
         .add_plugins(TrackingCameraPlugin)
         .add_plugins(ExhaustPlugin)
+        .add_plugins(ReentryGlowPlugin)
         .add_systems(OnEnter(GameState::Loading), load_assets)
         .add_systems(Update, main_menu.run_if(in_state(GameState::MainMenu)))
         .add_systems(Update, esc_pause.run_if(in_state(GameState::Running)))
         .add_systems(Update, esc_pause.run_if(in_state(GameState::Paused)))
         .add_systems(Update, pause_menu.run_if(in_state(GameState::Paused)))
+        .add_systems(Update, time_warp_hud.run_if(in_state(GameState::Running)))
+        .add_systems(Update, propellant_hud.run_if(in_state(GameState::Running)))
+        .add_systems(Update, heat_hud.run_if(in_state(GameState::Running)))
         .add_systems(OnEnter(GameState::Quit), exit_system)
         .add_systems(
             OnEnter(GameState::Running),
-            (tests::first_person::camera::spawn_player_ship,),
+            (
+                tests::first_person::camera::spawn_player_ship,
+                tests::first_person::system::spawn_solar_system,
+                tests::first_person::station::spawn_stations,
+            ),
         )
         .run();
 }
@@ -137,6 +207,59 @@ fn pause_menu(mut state: ResMut<NextState<GameState>>, mut egui_context: EguiCon
         });
 }
 
+fn time_warp_hud(warp: Res<TimeWarp>, mut egui_context: EguiContexts) {
+    egui::Area::new("time_warp_hud")
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10.0, 10.0))
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(format!("Time warp {:.0}x", warp.factor));
+            if warp.factor < warp.requested {
+                ui.label(format!("limited from {:.0}x", warp.requested));
+            }
+        });
+}
+
+fn propellant_hud(
+    ships: Query<(&DeltaV, Option<&PropellantTank>), With<PlayerControlled>>,
+    mut egui_context: EguiContexts,
+) {
+    let Ok((delta_v, tank)) = ships.get_single() else {
+        return;
+    };
+
+    egui::Area::new("propellant_hud")
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10.0, 50.0))
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(format!("Δv {:.1} m/s", delta_v.remaining));
+            if let Some(tank) = tank {
+                ui.label(format!("Propellant {:.0}%", tank.fill() * 100.0));
+            }
+        });
+}
+
+fn heat_hud(
+    ships: Query<(&ThermalStatus, Option<&HullIntegrity>), With<PlayerControlled>>,
+    mut egui_context: EguiContexts,
+) {
+    let Ok((status, hull)) = ships.get_single() else {
+        return;
+    };
+
+    egui::Area::new("heat_hud")
+        .anchor(egui::Align2::RIGHT_TOP, egui::Vec2::new(-10.0, 90.0))
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(format!("Heat {:.0}%", status.heat_level.max(0.0) * 100.0));
+            if status.is_overheating() {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    format!("OVERHEATING, thrust {:.0}%", status.thrust_limit * 100.0),
+                );
+            }
+            if let Some(hull) = hull {
+                ui.label(format!("Hull {:.0}%", hull.0 * 100.0));
+            }
+        });
+}
+
 fn esc_pause(
     mut state: ResMut<NextState<GameState>>,
     keys: Res<Input<KeyCode>>,
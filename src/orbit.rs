@@ -0,0 +1,174 @@
+use std::f64::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::physics::{OnRails, PhysicsSet, SimulationTime, Velocity};
+
+/// Classical Keplerian elements of an elliptic orbit around the entity's [Parent].
+/// Entities with this component are placed analytically by [orbit_system] from the current
+/// [SimulationTime], rather than integrated by the [velocity_system](crate::physics::velocity_system).
+/// Their position therefore doesn't drift, no matter how long the simulation runs.
+///
+/// The reference plane of the orbit is the parent's XZ-plane, and angles are in radians.
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct OrbitalElements {
+    /// Half of the longest diameter of the orbital ellipse, in world units.
+    pub semi_major_axis: f32,
+    /// `0.0` is a circular orbit, values approaching `1.0` are increasingly elongated.
+    pub eccentricity: f32,
+    /// Tilt of the orbital plane relative to the parent's XZ-plane.
+    pub inclination: f32,
+    /// Angle from the parent's X-axis to the point where the orbit crosses the reference plane going "up".
+    pub longitude_of_ascending_node: f32,
+    /// Angle from the ascending node to the point of closest approach, measured along the orbit.
+    pub argument_of_periapsis: f32,
+    /// Mean anomaly when the [SimulationTime] was zero.
+    pub mean_anomaly_at_epoch: f64,
+    /// Average angular speed of the body along its orbit, in radians per second.
+    pub mean_motion: f64,
+}
+
+impl Default for OrbitalElements {
+    fn default() -> Self {
+        Self {
+            semi_major_axis: 1.0,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            longitude_of_ascending_node: 0.0,
+            argument_of_periapsis: 0.0,
+            mean_anomaly_at_epoch: 0.0,
+            mean_motion: 0.0,
+        }
+    }
+}
+
+/// Position and velocity of an orbiting body relative to its parent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrbitalState {
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+impl OrbitalElements {
+    /// Circular orbit of the given radius which takes `period` seconds to complete.
+    pub fn circular(semi_major_axis: f32, period: f64) -> Self {
+        Self {
+            semi_major_axis,
+            mean_motion: TAU / period,
+            ..Default::default()
+        }
+    }
+
+    /// Mean motion of an orbit with the given semi-major axis around a body with
+    /// gravitational parameter `mu`, according to Kepler's third law.
+    pub fn mean_motion_around(mu: f32, semi_major_axis: f32) -> f64 {
+        (mu as f64 / (semi_major_axis as f64).powi(3)).sqrt()
+    }
+
+    /// Semi-major axis of an orbit around a body with gravitational parameter `mu`
+    /// which takes `period` seconds to complete, according to Kepler's third law.
+    pub fn semi_major_axis_around(mu: f32, period: f64) -> f32 {
+        (mu as f64 * (period / TAU).powi(2)).cbrt() as f32
+    }
+
+    /// Time in seconds it takes to complete one orbit.
+    pub fn period(&self) -> f64 {
+        TAU / self.mean_motion
+    }
+
+    /// Mean anomaly at the given simulation time, wrapped into `[0, 2π)`.
+    pub fn mean_anomaly(&self, time: f64) -> f64 {
+        (self.mean_anomaly_at_epoch + self.mean_motion * time).rem_euclid(TAU)
+    }
+
+    /// Position and velocity of the body relative to its parent at the given simulation time.
+    pub fn state_at(&self, time: f64) -> OrbitalState {
+        let e = self.eccentricity.clamp(0.0, 0.999_999) as f64;
+        let a = self.semi_major_axis as f64;
+        let eccentric_anomaly = solve_kepler(self.mean_anomaly(time), e);
+
+        let (sin_e, cos_e) = eccentric_anomaly.sin_cos();
+        let semi_minor = (1.0 - e * e).sqrt();
+        let rate = self.mean_motion / (1.0 - e * cos_e);
+
+        // Perifocal frame: periapsis along +X, motion counter-clockwise when seen from +Y.
+        let position = Vec3::new(
+            (a * (cos_e - e)) as f32,
+            0.0,
+            (-a * semi_minor * sin_e) as f32,
+        );
+        let velocity = Vec3::new(
+            (-a * sin_e * rate) as f32,
+            0.0,
+            (-a * semi_minor * cos_e * rate) as f32,
+        );
+
+        let orientation = self.orientation();
+        OrbitalState {
+            position: orientation * position,
+            velocity: orientation * velocity,
+        }
+    }
+
+    /// Rotation from the perifocal frame into the parent's frame.
+    pub fn orientation(&self) -> Quat {
+        Quat::from_rotation_y(self.longitude_of_ascending_node)
+            * Quat::from_rotation_x(self.inclination)
+            * Quat::from_rotation_y(self.argument_of_periapsis)
+    }
+}
+
+/// Solves Kepler's equation `M = E - e sin(E)` for the eccentric anomaly `E` using Newton's method.
+pub fn solve_kepler(mean_anomaly: f64, eccentricity: f64) -> f64 {
+    // Starting at π converges reliably for highly eccentric orbits.
+    let mut eccentric_anomaly = if eccentricity > 0.8 {
+        std::f64::consts::PI
+    } else {
+        mean_anomaly
+    };
+
+    for _ in 0..32 {
+        let (sin_e, cos_e) = eccentric_anomaly.sin_cos();
+        let delta = (eccentric_anomaly - eccentricity * sin_e - mean_anomaly)
+            / (1.0 - eccentricity * cos_e);
+        eccentric_anomaly -= delta;
+        if delta.abs() < 1e-12 {
+            break;
+        }
+    }
+
+    eccentric_anomaly
+}
+
+/// [Bundle](https://erasin.wang/books/bevy-cheatbook/programming/ec.html#component-bundles) for bodies moving on rails along a Keplerian orbit.
+#[derive(Bundle, Default)]
+pub struct OrbitBundle {
+    pub elements: OrbitalElements,
+    pub velocity: Velocity,
+    pub on_rails: OnRails,
+}
+
+pub struct OrbitPlugin;
+
+impl Plugin for OrbitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, orbit_system.in_set(PhysicsSet::Integrate))
+            .register_type::<OrbitalElements>();
+    }
+}
+
+/// Places every entity with [OrbitalElements] along its orbit according to the current
+/// [SimulationTime], and updates its [Velocity] (relative to its parent) if present.
+pub fn orbit_system(
+    time: Res<SimulationTime>,
+    mut query: Query<(&mut Transform, Option<&mut Velocity>, &OrbitalElements)>,
+) {
+    for (mut transform, velocity, elements) in query.iter_mut() {
+        let state = elements.state_at(time.elapsed);
+        transform.translation = state.position;
+
+        if let Some(mut velocity) = velocity {
+            velocity.0 = state.velocity;
+        }
+    }
+}
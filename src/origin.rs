@@ -0,0 +1,191 @@
+use bevy::{math::DVec3, prelude::*, transform::TransformSystem};
+
+use crate::controls::PlayerControlled;
+
+/// The world is divided into a grid of cubic cells, and the render origin sits at the center of
+/// one of them. Every root entity's authoritative position is its [GridPosition]: the (integer)
+/// grid cell it's in plus its `f32` offset from that cell. Its [Transform] holds the position
+/// relative to the render origin, which keeps every [Transform] near the [PlayerControlled] ship
+/// small, where `f32` is precise enough not to jitter, no matter how far the ship has travelled.
+#[derive(Debug, Clone, Resource, Reflect)]
+pub struct FloatingOrigin {
+    /// Grid cell the render origin currently sits in.
+    pub cell: IVec3,
+    /// Edge length of a grid cell in world units.
+    pub cell_size: f32,
+    /// Once the player is further than this from the origin along any axis, the origin is
+    /// moved to the grid cell the player is in.
+    pub threshold: f32,
+}
+
+impl Default for FloatingOrigin {
+    fn default() -> Self {
+        Self {
+            cell: IVec3::ZERO,
+            cell_size: 1000.0,
+            threshold: 1000.0,
+        }
+    }
+}
+
+impl FloatingOrigin {
+    /// High-precision position of a point given relative to the render origin.
+    pub fn world_position(&self, translation: Vec3) -> DVec3 {
+        self.cell.as_dvec3() * self.cell_size as f64 + translation.as_dvec3()
+    }
+
+    /// Converts a high-precision position into a translation relative to the render origin.
+    pub fn translation(&self, world_position: DVec3) -> Vec3 {
+        (world_position - self.cell.as_dvec3() * self.cell_size as f64).as_vec3()
+    }
+
+    /// Grid position of a point given relative to the render origin.
+    pub fn grid_position(&self, translation: Vec3) -> GridPosition {
+        let delta = (translation / self.cell_size).round().as_ivec3();
+        GridPosition {
+            cell: self.cell + delta,
+            offset: translation - delta.as_vec3() * self.cell_size,
+        }
+    }
+
+    /// Translation relative to the render origin of a point at `position`. Only the difference between
+    /// the cells is converted to `f32`, so points near the origin are exact however far out they are.
+    pub fn grid_translation(&self, position: &GridPosition) -> Vec3 {
+        (position.cell - self.cell).as_vec3() * self.cell_size + position.offset
+    }
+
+    /// Brings a root entity's [GridPosition] and [Transform] back in line after either was set from the
+    /// outside. A newly added grid position places the entity, otherwise a [Transform] which no longer
+    /// matches the grid position, for example after a teleport or a collision, moves the grid position to it.
+    pub fn sync(&self, grid: &mut Mut<GridPosition>, transform: &mut Mut<Transform>) {
+        if grid.is_added() {
+            transform.translation = self.grid_translation(grid);
+        } else if self.grid_translation(grid) != transform.translation {
+            grid.set_if_neq(self.grid_position(transform.translation));
+        }
+    }
+}
+
+/// Authoritative position of a root entity: the grid cell it's in, and its offset from the cell's center.
+/// Added to every root entity by [insert_grid_position_system]. Entities spawned with one are moved to it,
+/// which places them precisely however far from the render origin they are.
+///
+/// The [velocity_system](crate::physics::velocity_system) moves simulated entities by integrating their
+/// offset, which stays small however far out they are, and rebuilds their [Transform] from it. Whenever
+/// anything else moves the [Transform], the grid position is updated to match (see [FloatingOrigin::sync]).
+/// When the render origin moves, the [Transform] is rebuilt from the grid position, so entities which
+/// stay put, like far away stations, keep their exact position across any number of re-centers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Component, Reflect)]
+#[reflect(Component)]
+pub struct GridPosition {
+    pub cell: IVec3,
+    pub offset: Vec3,
+}
+
+impl GridPosition {
+    /// High-precision position in the world.
+    pub fn world_position(&self, cell_size: f32) -> DVec3 {
+        self.cell.as_dvec3() * cell_size as f64 + self.offset.as_dvec3()
+    }
+
+    /// Moves whole cells out of the offset into the cell, so the offset stays within half a cell.
+    pub fn normalize(&mut self, cell_size: f32) {
+        let delta = (self.offset / cell_size).round().as_ivec3();
+        self.cell += delta;
+        self.offset -= delta.as_vec3() * cell_size;
+    }
+}
+
+/// Sent when the render origin has moved. Every root [Transform] has been shifted by `-offset`, up to
+/// the rounding its [GridPosition] corrects.
+#[derive(Debug, Clone, Event)]
+pub struct OriginShifted {
+    pub previous: IVec3,
+    pub current: IVec3,
+    pub offset: Vec3,
+}
+
+pub struct FloatingOriginPlugin;
+
+impl Plugin for FloatingOriginPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FloatingOrigin>()
+            .add_event::<OriginShifted>()
+            .add_systems(Update, insert_grid_position_system)
+            .add_systems(
+                PostUpdate,
+                recenter_origin_system.before(TransformSystem::TransformPropagate),
+            )
+            .register_type::<FloatingOrigin>()
+            .register_type::<GridPosition>();
+    }
+}
+
+/// Gives every root entity without a [GridPosition] one matching its [Transform].
+#[allow(clippy::type_complexity)]
+pub fn insert_grid_position_system(
+    mut commands: Commands,
+    origin: Res<FloatingOrigin>,
+    roots: Query<(Entity, &Transform), (Without<Parent>, Without<GridPosition>)>,
+) {
+    for (entity, transform) in roots.iter() {
+        commands
+            .entity(entity)
+            .insert(origin.grid_position(transform.translation));
+    }
+}
+
+/// Places root entities spawned with a [GridPosition] and updates the [GridPosition] of every other
+/// root entity whose [Transform] was moved from the outside, then moves the render
+/// origin to the [PlayerControlled] entity's grid cell once it drifts past [FloatingOrigin::threshold].
+/// Root entities are then placed relative to the new origin from their [GridPosition], or shifted if
+/// they don't have one yet. Children follow along through the hierarchy, so velocities and orbits
+/// of entities parented to ships are unaffected.
+///
+/// This runs once per frame after all `FixedUpdate` ticks, before the [Transform]s are interpolated for
+/// rendering and propagated, so no system ever sees a [GlobalTransform] from before the shift next to
+/// a [Transform] from after it.
+pub fn recenter_origin_system(
+    mut origin: ResMut<FloatingOrigin>,
+    mut events: EventWriter<OriginShifted>,
+    player: Query<&GlobalTransform, With<PlayerControlled>>,
+    mut roots: Query<(&mut Transform, Option<&mut GridPosition>), Without<Parent>>,
+) {
+    for (mut transform, grid) in roots.iter_mut() {
+        if let Some(mut grid) = grid {
+            origin.sync(&mut grid, &mut transform);
+        }
+    }
+
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    let translation = player.translation();
+    if translation.abs().max_element() <= origin.threshold {
+        return;
+    }
+
+    let delta = (translation / origin.cell_size).round().as_ivec3();
+    if delta == IVec3::ZERO {
+        return;
+    }
+
+    let previous = origin.cell;
+    origin.cell += delta;
+    let offset = delta.as_vec3() * origin.cell_size;
+
+    for (mut transform, grid) in roots.iter_mut() {
+        transform.translation = match grid {
+            Some(grid) => origin.grid_translation(&grid),
+            None => transform.translation - offset,
+        };
+    }
+
+    debug!("floating origin moved from {} to {}", previous, origin.cell);
+    events.send(OriginShifted {
+        previous,
+        current: origin.cell,
+        offset,
+    });
+}
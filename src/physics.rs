@@ -1,11 +1,21 @@
 use std::ops::Deref;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, time::Fixed};
+
+use crate::{
+    origin::{FloatingOrigin, GridPosition},
+    sleep::Sleeping,
+    snapshot::ReflectSnapshot,
+    warp::TimeWarp,
+};
 
 //use crate::debug::{debug_vector_system, DebuggableValue};
 
-/// Translational velocity of the entity. Integrated by [velocity_system] into the entity [Transform]'s translational component.
-#[derive(Debug, Default, Component, Reflect)]
+/// Translational velocity of the entity relative to its parent, in the parent's frame. Integrated by [velocity_system]
+/// into the entity [Transform]'s translational component. See [world_motion](crate::kinematics::world_motion)
+/// for the velocity through the world, which includes the motion of the parent.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
 pub struct Velocity(pub Vec3);
 
 impl Deref for Velocity {
@@ -15,8 +25,10 @@ impl Deref for Velocity {
     }
 }
 
-/// Angular velocity of the entity. Integrated by [angular_velocity_system] into the entity [Transform]'s rotational component.
-#[derive(Debug, Default, Component, Reflect)]
+/// Angular velocity of the entity relative to its parent, in the parent's frame.
+/// Integrated by [angular_velocity_system] into the entity [Transform]'s rotational component.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
 pub struct AngularVelocity(pub Vec3);
 
 impl Deref for AngularVelocity {
@@ -26,15 +38,17 @@ impl Deref for AngularVelocity {
     }
 }
 
-/// The [drag_system] uses this component to dampen the [Velocity] and [AngularVelocity] components
-#[derive(Debug, Default, Component, Reflect)]
+/// The [velocity_system] and [angular_velocity_system] use this component to exponentially dampen
+/// the [Velocity] and [AngularVelocity] components: every second, velocities decay by a factor of `e^-drag`.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
 pub struct Drag(pub f32);
 
-/// Defines the *intended* acceleration of an entity. This is integrated into [Velocity] by [acceleration_system]
-/// Some systems directly overwrite this component's value.
-/// for example [Impulse](crate::ship::Impulse) or [AngularImpulse](crate::ship::AngularImpulse) on the ship.
-/// This is the primary way in which player controls & target tracking works.
-#[derive(Debug, Default, Component, Reflect)]
+/// Defines the acceleration of an entity. This is integrated into [Velocity] by [velocity_system]
+/// For entities with a [Force] component, the value is recomputed every tick by [force_resolve_system],
+/// so systems should generally add to [Force] rather than write this component directly.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
 pub struct Acceleration(pub Vec3);
 
 impl Deref for Acceleration {
@@ -44,8 +58,10 @@ impl Deref for Acceleration {
     }
 }
 
-/// Angular acceleration of the entity. Integrated by [angular_acceleration_system] into the entity's [AngularVelocity] component.
-#[derive(Debug, Default, Component, Reflect)]
+/// Angular acceleration of the entity. Integrated by [angular_velocity_system] into the entity's [AngularVelocity] component.
+/// Like [Acceleration], recomputed every tick from [Torque] by [torque_resolve_system] if present.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
 pub struct AngularAcceleration(pub Vec3);
 
 impl Deref for AngularAcceleration {
@@ -55,40 +71,288 @@ impl Deref for AngularAcceleration {
     }
 }
 
+/// Mass of the entity in kilograms. [force_resolve_system] divides the accumulated [Force] by this
+/// value, so the same thrust moves a heavily laden freighter less than an empty one.
+/// Systems are free to modify it at runtime, for example when cargo is loaded or fuel is burned.
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct Mass(pub f32);
+
+impl Default for Mass {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Moment of inertia tensor of the entity in kg·m², expressed in the entity's local frame.
+/// Used by [torque_resolve_system] to turn [Torque] into [AngularAcceleration].
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+pub struct Inertia(pub Mat3);
+
+impl Default for Inertia {
+    fn default() -> Self {
+        Self(Mat3::IDENTITY)
+    }
+}
+
+impl Inertia {
+    /// Inertia tensor of a solid sphere of uniform density.
+    pub fn solid_sphere(mass: f32, radius: f32) -> Self {
+        Self(Mat3::from_diagonal(Vec3::splat(
+            0.4 * mass * radius * radius,
+        )))
+    }
+
+    /// Inertia tensor of a solid box of uniform density with the given half extents.
+    pub fn solid_cuboid(mass: f32, half_extents: Vec3) -> Self {
+        let size = half_extents * 2.0;
+        let sq = size * size;
+        Self(Mat3::from_diagonal(
+            Vec3::new(sq.y + sq.z, sq.x + sq.z, sq.x + sq.y) * (mass / 12.0),
+        ))
+    }
+
+    /// Inertia tensor rotated into world space by the entity's current `rotation`.
+    pub fn world(&self, rotation: Quat) -> Mat3 {
+        let rotation = Mat3::from_quat(rotation);
+        rotation * self.0 * rotation.transpose()
+    }
+}
+
+/// World-space force in newtons accumulated during the current tick.
+/// Any system running in [PhysicsSet::Forces] can add to it. It is turned into [Acceleration]
+/// and reset to zero by [force_resolve_system].
+#[derive(Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Force(pub Vec3);
+
+/// World-space torque in newton-metres accumulated during the current tick.
+/// Any system running in [PhysicsSet::Forces] can add to it. It is turned into [AngularAcceleration]
+/// and reset to zero by [torque_resolve_system].
+#[derive(Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct Torque(pub Vec3);
+
+impl Force {
+    /// Adds a world-space `force` applied at `offset` from the entity's center of mass,
+    /// accumulating the resulting moment into `torque`.
+    pub fn add_at_offset(&mut self, torque: &mut Torque, force: Vec3, offset: Vec3) {
+        self.0 += force;
+        torque.0 += offset.cross(force);
+    }
+}
+
 /// [Bundle](https://erasin.wang/books/bevy-cheatbook/programming/ec.html#component-bundles) containing common physics components.
 #[derive(Bundle, Default)]
 pub struct PhysicsBundle {
     pub drag: Drag,
+    pub mass: Mass,
+    pub inertia: Inertia,
+    pub force: Force,
+    pub torque: Torque,
     pub velocity: Velocity,
     pub acceleration: Acceleration,
     pub angular_velocity: AngularVelocity,
     pub angular_acceleration: AngularAcceleration,
 }
 
+/// Marks an entity whose motion is prescribed analytically (for instance by an [OrbitalElements](crate::orbit::OrbitalElements)
+/// component) rather than simulated. [velocity_system] leaves these entities alone, but their [Velocity] is
+/// kept up to date by whichever system moves them, so other systems can still read it.
+#[derive(Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct OnRails;
+
+/// Simulation time in seconds since the start of the game, advanced once per physics tick by
+/// [simulation_time_system]. Stored as `f64` so analytic motion stays precise over long sessions.
+#[derive(Debug, Default, Clone, Resource, Reflect)]
+pub struct SimulationTime {
+    pub elapsed: f64,
+    /// Number of physics ticks simulated so far.
+    pub tick: u64,
+    /// Duration of the current tick in seconds. This is the configured fixed timestep rather than
+    /// the measured frame time, so the same inputs always produce the same trajectory.
+    /// During [TimeWarp](crate::warp::TimeWarp) it is scaled up, see [TimeWarp::tick_delta](crate::warp::TimeWarp::tick_delta).
+    pub delta: f32,
+}
+
+/// Stages of a physics tick in the `FixedUpdate` schedule. Each set runs after the previous one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub enum PhysicsSet {
+    /// Systems deciding what bodies want to do this tick, such as player input and autopilots.
+    /// They set [Impulse](crate::impulse::Impulse) and [AngularImpulse](crate::impulse::AngularImpulse).
+    Control,
+    /// Systems accumulating [Force] and [Torque] for the current tick.
+    Forces,
+    /// Turns accumulated forces into [Acceleration] and [AngularAcceleration].
+    Resolve,
+    /// Integrates accelerations and velocities into the entity [Transform].
+    Integrate,
+    /// Detects collisions between the freshly integrated [Transform]s.
+    Collide,
+    /// Resolves detected collisions by changing velocities and pushing bodies apart.
+    Solve,
+}
+
+/// Selects the numerical scheme used by [velocity_system] and [angular_velocity_system]
+/// to advance the entity's state by one `FixedUpdate` tick.
+///
+/// All schemes apply [Drag] as exact exponential decay (see [KinematicState::drift]), so
+/// an entity's terminal velocity no longer depends on the tick rate. [Integrator::VelocityVerlet]
+/// and [Integrator::RungeKutta4] are exact for constant accelerations, meaning a ship under
+/// constant thrust ends up in the same place regardless of the `FixedUpdate` timestep.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource, Reflect)]
+pub enum Integrator {
+    /// Advances the position using the velocity at the *start* of the tick.
+    ExplicitEuler,
+    /// Advances the velocity first, and then the position using the updated velocity.
+    /// This is what the physics systems did before integrators were selectable.
+    #[default]
+    SemiImplicitEuler,
+    /// Second order scheme averaging the acceleration at the start and end of the tick.
+    VelocityVerlet,
+    /// Classic four-stage Runge-Kutta, with the position update using Runge-Kutta-Nyström weights.
+    RungeKutta4,
+}
+
+/// Position/velocity pair advanced by an [Integrator]. Used for both the translational state
+/// of an entity and its rotational state, in which case `position` is a rotation vector
+/// (axis scaled by angle) relative to the orientation at the start of the tick.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct KinematicState {
+    pub position: Vec3,
+    pub velocity: Vec3,
+}
+
+impl KinematicState {
+    pub fn new(position: Vec3, velocity: Vec3) -> Self {
+        Self { position, velocity }
+    }
+
+    /// Exact solution of `x' = v, v' = acceleration - drag * v` over `dt`,
+    /// assuming the acceleration stays constant over the interval.
+    pub fn drift(self, acceleration: Vec3, drag: f32, dt: f32) -> Self {
+        let kdt = drag * dt;
+        let decay = (-kdt).exp();
+
+        // phi = (1 - e^(-kdt)) / k and psi = (dt - phi) / k, with their limits as drag -> 0.
+        // The series expansions avoid catastrophic cancellation for very small drag.
+        let (phi, psi) = if kdt.abs() < 1e-3 {
+            (
+                dt * (1.0 - kdt * 0.5 + kdt * kdt / 6.0),
+                dt * dt * (0.5 - kdt / 6.0 + kdt * kdt / 24.0),
+            )
+        } else {
+            let phi = -(-kdt).exp_m1() / drag;
+            (phi, (dt - phi) / drag)
+        };
+
+        Self {
+            position: self.position + self.velocity * phi + acceleration * psi,
+            velocity: self.velocity * decay + acceleration * phi,
+        }
+    }
+}
+
+impl Integrator {
+    /// Advances `state` by `dt` seconds. `acceleration` is sampled once per stage of the scheme,
+    /// which lets callers (like trajectory prediction) supply position-dependent accelerations.
+    pub fn step(
+        self,
+        state: KinematicState,
+        drag: f32,
+        dt: f32,
+        mut acceleration: impl FnMut(KinematicState) -> Vec3,
+    ) -> KinematicState {
+        match self {
+            Integrator::ExplicitEuler => {
+                let a = acceleration(state);
+                KinematicState {
+                    position: state.position + state.velocity * dt,
+                    velocity: state.drift(a, drag, dt).velocity,
+                }
+            }
+            Integrator::SemiImplicitEuler => {
+                let a = acceleration(state);
+                let velocity = state.drift(a, drag, dt).velocity;
+                KinematicState {
+                    position: state.position + velocity * dt,
+                    velocity,
+                }
+            }
+            Integrator::VelocityVerlet => {
+                let a0 = acceleration(state);
+                let predicted = state.drift(a0, drag, dt);
+                let a1 = acceleration(predicted);
+                KinematicState {
+                    position: predicted.position,
+                    velocity: state.drift((a0 + a1) * 0.5, drag, dt).velocity,
+                }
+            }
+            Integrator::RungeKutta4 => {
+                let half = dt * 0.5;
+                let a1 = acceleration(state);
+                let a2 = acceleration(state.drift(a1, drag, half));
+                let a3 = acceleration(state.drift(a2, drag, half));
+                let a4 = acceleration(state.drift(a3, drag, dt));
+                KinematicState {
+                    position: state.drift((a1 + a2 + a3) / 3.0, drag, dt).position,
+                    velocity: state
+                        .drift((a1 + (a2 + a3) * 2.0 + a4) / 6.0, drag, dt)
+                        .velocity,
+                }
+            }
+        }
+    }
+}
+
 /// Adds the `Physics` systems to the application, and defines
 /// some of the physics components as [DebuggableValue](crate::debug::DebuggableValue)s (see: [AddDebugValue](crate::debug::AddDebugValue))
 pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            FixedUpdate,
-            (acceleration_system, drag_system, velocity_system).chain(),
-        )
-        .add_systems(
-            FixedUpdate,
-            (
-                angular_acceleration_system,
-                angular_drag_system,
-                angular_velocity_system,
+        app.init_resource::<Integrator>()
+            .init_resource::<SimulationTime>()
+            .configure_sets(
+                FixedUpdate,
+                (
+                    PhysicsSet::Control,
+                    PhysicsSet::Forces,
+                    PhysicsSet::Resolve,
+                    PhysicsSet::Integrate,
+                    PhysicsSet::Collide,
+                    PhysicsSet::Solve,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                simulation_time_system.before(PhysicsSet::Control),
             )
-                .chain(),
-        )
-        .register_type::<Drag>()
-        .register_type::<Velocity>()
-        .register_type::<Acceleration>()
-        .register_type::<AngularVelocity>()
-        .register_type::<AngularAcceleration>();
+            .add_systems(
+                FixedUpdate,
+                (force_resolve_system, torque_resolve_system).in_set(PhysicsSet::Resolve),
+            )
+            .add_systems(
+                FixedUpdate,
+                (velocity_system, angular_velocity_system)
+                    .chain()
+                    .in_set(PhysicsSet::Integrate),
+            )
+            .register_type::<Integrator>()
+            .register_type::<SimulationTime>()
+            .register_type::<OnRails>()
+            .register_type::<Drag>()
+            .register_type::<Mass>()
+            .register_type::<Inertia>()
+            .register_type::<Force>()
+            .register_type::<Torque>()
+            .register_type::<Velocity>()
+            .register_type::<Acceleration>()
+            .register_type::<AngularVelocity>()
+            .register_type::<AngularAcceleration>();
         // .add_plugin(DebuggableValue::<Transform>::default())
         // .add_plugin(DebuggableValue::<Velocity>::default())
         // .add_plugin(DebuggableValue::<Acceleration>::default())
@@ -99,65 +363,157 @@ impl Plugin for PhysicsPlugin {
     }
 }
 
-/// Integrates the [Velocity] into the entity [Transform]'s translational component
-pub fn velocity_system(time: Res<Time>, mut query: Query<(&mut Transform, &Velocity)>) {
-    for (mut transform, velocity) in query.iter_mut() {
-        transform.translation += velocity.0 * time.delta_seconds();
-    }
+/// Advances [SimulationTime] by one fixed timestep, or by the part of a warped timestep one tick covers.
+pub fn simulation_time_system(
+    fixed: Res<Time<Fixed>>,
+    warp: Option<Res<TimeWarp>>,
+    mut simulation_time: ResMut<SimulationTime>,
+) {
+    let timestep = fixed.timestep().as_secs_f64();
+    let delta = warp.map_or(timestep, |warp| warp.tick_delta(timestep));
+    simulation_time.delta = delta as f32;
+    simulation_time.elapsed += delta;
+    simulation_time.tick += 1;
 }
 
-/// Integrates the [AngularVelocity] into the entity [Transform]'s rotational component
-pub fn angular_velocity_system(
-    time: Res<Time>,
-    mut query: Query<(&mut Transform, &AngularVelocity)>,
+/// Turns the [Force] accumulated during this tick into [Acceleration] according to the entity's [Mass],
+/// then clears the accumulator for the next tick. Entities without a [Mass] are treated as weighing 1kg.
+/// [Sleeping] entities are skipped.
+pub fn force_resolve_system(
+    mut query: Query<(&mut Acceleration, &mut Force, Option<&Mass>), Without<Sleeping>>,
 ) {
-    for (mut transform, angular_velocity) in query.iter_mut() {
-        let scaled = angular_velocity.0 * time.delta_seconds();
-        if scaled.length() > 0.0 {
-            let velocity_as_quat =
-                Quat::from_axis_angle(scaled.normalize(), scaled.length()).normalize();
-            transform.rotate(velocity_as_quat);
-        }
+    for (mut acceleration, mut force, mass) in query.iter_mut() {
+        let mass = mass.map(|m| m.0).unwrap_or(1.0);
+        acceleration.0 = if mass > 0.0 {
+            force.0 / mass
+        } else {
+            Vec3::ZERO
+        };
+        force.0 = Vec3::ZERO;
     }
 }
 
-/// Linearly interpolates between ([Velocity] & [AngularVelocity]) and [Vec3::ZERO]
-/// by a factor of [Drag] * `time.delta_seconds()`, effectively slowing the respective velocities.
-/// I think timestep might affect this strangely. Assuming a 0.5s frametime, the system
-/// will halve the velocities of the entity, whereas a frametime >= 1.0 will immediately stop or
-/// even reverse the velocity of the object.
-pub fn drag_system(time: Res<Time>, mut query: Query<(&mut Velocity, &Drag)>) {
-    for (mut velocity, drag) in query.iter_mut() {
-        velocity.0 = velocity.0.lerp(Vec3::ZERO, drag.0 * time.delta_seconds());
-    }
-}
+/// Turns the [Torque] accumulated during this tick into [AngularAcceleration] using the entity's [Inertia]
+/// tensor rotated into world space, including the gyroscopic term `ω × Iω`. Clears the accumulator afterwards.
+/// [Sleeping] entities are skipped.
+#[allow(clippy::type_complexity)]
+pub fn torque_resolve_system(
+    mut query: Query<
+        (
+            &mut AngularAcceleration,
+            &mut Torque,
+            &Transform,
+            Option<&Inertia>,
+            Option<&AngularVelocity>,
+        ),
+        Without<Sleeping>,
+    >,
+) {
+    for (mut acceleration, mut torque, transform, inertia, angular_velocity) in query.iter_mut() {
+        let inertia = inertia
+            .copied()
+            .unwrap_or_default()
+            .world(transform.rotation);
+        let omega = angular_velocity.map(|v| v.0).unwrap_or(Vec3::ZERO);
 
-/// Linearly interpolates between ([Velocity] & [AngularVelocity]) and [Vec3::ZERO]
-/// by a factor of [Drag] * `time.delta_seconds()`, effectively slowing the respective velocities.
-/// I think timestep might affect this strangely. Assuming a 0.5s frametime, the system
-/// will halve the velocities of the entity, whereas a frametime >= 1.0 will immediately stop or
-/// even reverse the velocity of the object.
-pub fn angular_drag_system(time: Res<Time>, mut query: Query<(&mut AngularVelocity, &Drag)>) {
-    for (mut angular_velocity, drag) in query.iter_mut() {
-        angular_velocity.0 = angular_velocity
-            .0
-            .lerp(Vec3::ZERO, drag.0 * time.delta_seconds());
+        acceleration.0 = if inertia.determinant().is_normal() {
+            inertia.inverse() * (torque.0 - omega.cross(inertia * omega))
+        } else {
+            Vec3::ZERO
+        };
+        torque.0 = Vec3::ZERO;
     }
 }
 
-/// Integrates the [AngularAcceleration] into [AngularVelocity]
-pub fn angular_acceleration_system(
-    time: Res<Time>,
-    mut query: Query<(&mut AngularVelocity, &AngularAcceleration)>,
+/// Integrates [Acceleration] into [Velocity], and [Velocity] into the entity [Transform]'s translational component,
+/// using the selected [Integrator]. [Drag] is applied as exponential decay of the velocity.
+/// Entities marked [OnRails] or [Sleeping] are skipped.
+///
+/// Root entities with a [GridPosition] move within their grid cell instead, and their [Transform] is rebuilt
+/// from the [GridPosition], so they move just as precisely far from the [FloatingOrigin] as next to it.
+#[allow(clippy::type_complexity)]
+pub fn velocity_system(
+    time: Res<SimulationTime>,
+    integrator: Res<Integrator>,
+    origin: Option<Res<FloatingOrigin>>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut Velocity,
+            Option<&Acceleration>,
+            Option<&Drag>,
+            Option<&mut GridPosition>,
+            Has<Parent>,
+        ),
+        (Without<OnRails>, Without<Sleeping>),
+    >,
 ) {
-    for (mut velocity, acceleration) in query.iter_mut() {
-        velocity.0 += acceleration.0 * time.delta_seconds();
+    let dt = time.delta;
+    for (mut transform, mut velocity, acceleration, drag, grid, parented) in query.iter_mut() {
+        let acceleration = acceleration.map(|a| a.0).unwrap_or(Vec3::ZERO);
+        let drag = drag.map(|d| d.0).unwrap_or(0.0);
+
+        // A GridPosition left over from before the entity was parented is stale.
+        let mut grid = match (origin.as_deref(), grid) {
+            (Some(origin), Some(grid)) if !parented => Some((origin, grid)),
+            _ => None,
+        };
+        let position = match &mut grid {
+            Some((origin, grid)) => {
+                origin.sync(grid, &mut transform);
+                grid.offset
+            }
+            None => transform.translation,
+        };
+
+        let state = integrator.step(KinematicState::new(position, velocity.0), drag, dt, |_| {
+            acceleration
+        });
+
+        match grid {
+            Some((origin, mut grid)) => {
+                grid.offset = state.position;
+                grid.normalize(origin.cell_size);
+                transform.translation = origin.grid_translation(&grid);
+            }
+            None => transform.translation = state.position,
+        }
+        velocity.0 = state.velocity;
     }
 }
 
-/// Integrates [Acceleration] into [Velocity]
-pub fn acceleration_system(time: Res<Time>, mut query: Query<(&mut Velocity, &Acceleration)>) {
-    for (mut velocity, acceleration) in query.iter_mut() {
-        velocity.0 += acceleration.0 * time.delta_seconds();
+/// Integrates [AngularAcceleration] into [AngularVelocity], and [AngularVelocity] into the entity [Transform]'s
+/// rotational component, using the selected [Integrator]. [Drag] is applied as exponential decay of the angular velocity.
+/// [Sleeping] entities are skipped.
+#[allow(clippy::type_complexity)]
+pub fn angular_velocity_system(
+    time: Res<SimulationTime>,
+    integrator: Res<Integrator>,
+    mut query: Query<
+        (
+            &mut Transform,
+            &mut AngularVelocity,
+            Option<&AngularAcceleration>,
+            Option<&Drag>,
+        ),
+        Without<Sleeping>,
+    >,
+) {
+    let dt = time.delta;
+    for (mut transform, mut angular_velocity, acceleration, drag) in query.iter_mut() {
+        let acceleration = acceleration.map(|a| a.0).unwrap_or(Vec3::ZERO);
+        let drag = drag.map(|d| d.0).unwrap_or(0.0);
+
+        let state = integrator.step(
+            KinematicState::new(Vec3::ZERO, angular_velocity.0),
+            drag,
+            dt,
+            |_| acceleration,
+        );
+
+        if state.position.length_squared() > 0.0 {
+            transform.rotate(Quat::from_scaled_axis(state.position).normalize());
+        }
+        angular_velocity.0 = state.velocity;
     }
 }
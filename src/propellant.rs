@@ -0,0 +1,237 @@
+use bevy::prelude::*;
+
+use crate::{
+    allocation::ThrustAllocation,
+    impulse::{impulse_system, virtual_engine_force},
+    physics::{Mass, PhysicsSet, SimulationTime},
+    snapshot::ReflectSnapshot,
+    thruster::{thruster_force_system, Thruster, Thrusters},
+};
+
+/// Standard gravity in m/s², which converts a [SpecificImpulse] into an exhaust velocity.
+pub const STANDARD_GRAVITY: f32 = 9.80665;
+
+/// Specific impulse used for engines without a [SpecificImpulse] of their own or on their vessel.
+pub const DEFAULT_SPECIFIC_IMPULSE: f32 = 300.0;
+
+/// Propellant stored aboard a ship. A tank on a [Thruster] entity feeds only that engine,
+/// a tank on the vessel feeds all of its engines which don't have their own.
+/// Ships without any tank have unlimited propellant.
+///
+/// The propellant is part of the vessel's [Mass], which drops as [propellant_system] burns it.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct PropellantTank {
+    /// Most propellant the tank holds, in kilograms.
+    pub capacity: f32,
+    /// Propellant currently in the tank, in kilograms.
+    pub mass: f32,
+}
+
+impl PropellantTank {
+    /// A tank filled to its `capacity`.
+    pub fn full(capacity: f32) -> Self {
+        Self {
+            capacity,
+            mass: capacity,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mass <= 0.0
+    }
+
+    /// Fraction of the capacity left, between `0.0` and `1.0`.
+    pub fn fill(&self) -> f32 {
+        if self.capacity > 0.0 {
+            (self.mass / self.capacity).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Fuel efficiency of an engine in seconds: it burns `thrust / (specific_impulse * g0)` kilograms
+/// of propellant per second. Set per engine or per vessel, see [Thrusters].
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+pub struct SpecificImpulse(pub f32);
+
+impl SpecificImpulse {
+    /// Speed of the exhaust, in world units per second.
+    pub fn exhaust_velocity(&self) -> f32 {
+        self.0 * STANDARD_GRAVITY
+    }
+}
+
+/// Velocity change a ship can still achieve by burning all its propellant, from the rocket equation.
+/// Updated every tick by [propellant_system] for ships with a [PropellantTank].
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+pub struct DeltaV {
+    /// Remaining delta-v, in world units per second.
+    pub remaining: f32,
+    /// Effective exhaust velocity of all the ship's fueled engines firing together.
+    pub exhaust_velocity: f32,
+}
+
+impl DeltaV {
+    /// Propellant a ship of `mass` kilograms burns to change its velocity by `delta_v`.
+    pub fn propellant_for(&self, delta_v: f32, mass: f32) -> f32 {
+        if self.exhaust_velocity <= 0.0 {
+            return f32::INFINITY;
+        }
+        mass * -(-delta_v / self.exhaust_velocity).exp_m1()
+    }
+}
+
+/// Tsiolkovsky rocket equation: velocity change from burning a ship of mass `wet` down to `dry`.
+pub fn rocket_equation(exhaust_velocity: f32, wet: f32, dry: f32) -> f32 {
+    if wet <= 0.0 || dry <= 0.0 {
+        return 0.0;
+    }
+    exhaust_velocity * (wet / dry).ln()
+}
+
+/// Tank an engine draws from: its own, or else its vessel's.
+pub fn feeding_tank(
+    thruster: Option<Entity>,
+    vessel: Entity,
+    tanks: &Query<&PropellantTank>,
+) -> Option<Entity> {
+    thruster
+        .filter(|thruster| tanks.contains(*thruster))
+        .or_else(|| tanks.contains(vessel).then_some(vessel))
+}
+
+/// Whether an engine still has propellant. Engines without a tank always do.
+pub fn has_propellant(
+    thruster: Option<Entity>,
+    vessel: Entity,
+    tanks: &Query<&PropellantTank>,
+) -> bool {
+    !feeding_tank(thruster, vessel, tanks)
+        .and_then(|tank| tanks.get(tank).ok())
+        .is_some_and(PropellantTank::is_empty)
+}
+
+pub struct PropellantPlugin;
+
+impl Plugin for PropellantPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            propellant_system
+                .after(impulse_system)
+                .before(thruster_force_system)
+                .in_set(PhysicsSet::Forces),
+        )
+        .register_type::<SpecificImpulse>()
+        .register_type::<DeltaV>()
+        .register_type::<PropellantTank>();
+    }
+}
+
+/// Burns the propellant for the thrust [impulse_system] allocated this tick, and lowers each vessel's
+/// [Mass] accordingly. Thrusters burn according to their [Thruster::output], the virtual engines along
+/// directions without [Thrusters] according to the rest of the [ThrustAllocation::achieved_force].
+/// Reaction wheels run on electricity and don't use propellant.
+///
+/// Afterwards, the [DeltaV] of every ship with a tank is recomputed.
+#[allow(clippy::type_complexity)]
+pub fn propellant_system(
+    mut commands: Commands,
+    time: Res<SimulationTime>,
+    mut vessels: Query<
+        (
+            Entity,
+            &mut Mass,
+            Option<&Thrusters>,
+            Option<&ThrustAllocation>,
+            Option<&SpecificImpulse>,
+            Option<&mut DeltaV>,
+        ),
+        Or<(With<Thrusters>, With<ThrustAllocation>)>,
+    >,
+    thrusters: Query<(&Thruster, Option<&SpecificImpulse>)>,
+    mut tanks: Query<&mut PropellantTank>,
+) {
+    for (vessel, mut mass, owned, allocation, vessel_isp, delta_v) in vessels.iter_mut() {
+        let vessel_isp = vessel_isp
+            .copied()
+            .unwrap_or(SpecificImpulse(DEFAULT_SPECIFIC_IMPULSE));
+
+        // Every engine as (tank it draws from, thrust now, thrust at full throttle, specific impulse).
+        let mut engines: Vec<(Option<Entity>, f32, f32, SpecificImpulse)> = owned
+            .iter()
+            .flat_map(|owned| owned.0.iter())
+            .filter_map(|entity| {
+                let (thruster, isp) = thrusters.get(*entity).ok()?;
+                Some((
+                    feeding_tank(Some(*entity), vessel, &tanks.to_readonly()),
+                    thruster.output(),
+                    thruster.max_force * thruster.boost * thruster.condition.clamp(0.0, 1.0),
+                    isp.copied().unwrap_or(vessel_isp),
+                ))
+            })
+            .collect();
+
+        let owned_thrusters = owned
+            .iter()
+            .flat_map(|owned| owned.0.iter())
+            .filter_map(|entity| thrusters.get(*entity).ok())
+            .map(|(thruster, _)| thruster);
+        let force = virtual_engine_force(allocation, owned_thrusters).abs();
+        let tank = feeding_tank(None, vessel, &tanks.to_readonly());
+        engines.extend(
+            [force.x, force.y, force.z]
+                .into_iter()
+                .map(|thrust| (tank, thrust, thrust, vessel_isp)),
+        );
+
+        let mut total_thrust = 0.0;
+        let mut total_flow = 0.0;
+        for (tank, thrust, max_thrust, isp) in engines.iter() {
+            let Some(mut tank) = tank.and_then(|tank| tanks.get_mut(tank).ok()) else {
+                continue;
+            };
+
+            let burned = (thrust / isp.exhaust_velocity() * time.delta).min(tank.mass.max(0.0));
+            tank.mass -= burned;
+            mass.0 = (mass.0 - burned).max(f32::EPSILON);
+
+            if !tank.is_empty() {
+                total_thrust += max_thrust;
+                total_flow += max_thrust / isp.exhaust_velocity();
+            }
+        }
+
+        let mut feeding: Vec<Entity> = engines.iter().filter_map(|(tank, ..)| *tank).collect();
+        feeding.sort();
+        feeding.dedup();
+        if feeding.is_empty() {
+            continue;
+        }
+
+        let propellant: f32 = feeding
+            .iter()
+            .filter_map(|tank| tanks.get(*tank).ok())
+            .map(|tank| tank.mass.max(0.0))
+            .sum();
+
+        let exhaust_velocity = if total_flow > 0.0 {
+            total_thrust / total_flow
+        } else {
+            vessel_isp.exhaust_velocity()
+        };
+        let updated = DeltaV {
+            remaining: rocket_equation(exhaust_velocity, mass.0, mass.0 - propellant),
+            exhaust_velocity,
+        };
+
+        match delta_v {
+            Some(mut delta_v) => *delta_v = updated,
+            None => {
+                commands.entity(vessel).insert(updated);
+            }
+        }
+    }
+}
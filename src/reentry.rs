@@ -0,0 +1,157 @@
+use bevy::{
+    app::{Plugin, Startup, Update},
+    asset::{AssetServer, Assets, Handle},
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::Added,
+        system::{Commands, Query, Res, ResMut, Resource},
+    },
+    hierarchy::BuildChildren,
+    math::{Vec2, Vec3, Vec4},
+    render::texture::Image,
+    transform::components::Transform,
+};
+use bevy_hanabi::{
+    Attribute, ColorOverLifetimeModifier, CompiledParticleEffect, EffectAsset, EffectSpawner,
+    ExprWriter, Gradient, ImageSampleMapping, OrientMode, OrientModifier, ParticleEffect,
+    ParticleEffectBundle, ParticleTextureModifier, SetAttributeModifier, SetPositionSphereModifier,
+    SizeOverLifetimeModifier, Spawner,
+};
+
+use crate::atmosphere::{Aerodynamics, AtmosphericConditions};
+
+/// Heat flux in watts above which a re-entering entity starts to glow.
+const GLOW_THRESHOLD: f32 = 0.5;
+
+/// Particle emitter making the entity with [Aerodynamics] it belongs to glow while it heats up.
+#[derive(Clone, Component)]
+pub struct ReentryGlow {
+    parent_entity: Entity,
+}
+
+#[derive(Default, Clone, Resource)]
+struct ReentryGlowEffect {
+    handle: Handle<EffectAsset>,
+}
+
+pub struct ReentryGlowPlugin;
+
+impl Plugin for ReentryGlowPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.insert_resource(ReentryGlowEffect::default())
+            .add_systems(Startup, create_reentry_glow_effect)
+            .add_systems(Update, (attach_reentry_glow, update_reentry_glow_velocity));
+    }
+}
+
+fn create_reentry_glow_effect(
+    mut effects: ResMut<Assets<EffectAsset>>,
+    mut glow_handle: ResMut<ReentryGlowEffect>,
+    asset_server: Res<AssetServer>,
+) {
+    let particle_texture: Handle<Image> = asset_server.load("images/cloud.png");
+
+    let mut color_gradient = Gradient::new();
+    color_gradient.add_key(0.0, Vec4::new(1.0, 1.0, 0.8, 0.0));
+    color_gradient.add_key(0.1, Vec4::new(1.0, 0.8, 0.4, 0.6));
+    color_gradient.add_key(0.4, Vec4::new(1.0, 0.4, 0.1, 0.4));
+    color_gradient.add_key(1.0, Vec4::new(0.6, 0.1, 0.0, 0.0));
+
+    let mut size_gradient = Gradient::new();
+    size_gradient.add_key(0.0, Vec2::new(0.1, 0.1));
+    size_gradient.add_key(0.3, Vec2::new(0.3, 0.3));
+    size_gradient.add_key(1.0, Vec2::new(0.05, 0.05));
+
+    let writer = ExprWriter::new();
+
+    let init_position = SetPositionSphereModifier {
+        center: writer.lit(Vec3::ZERO).expr(),
+        radius: writer.lit(0.5).expr(),
+        dimension: bevy_hanabi::ShapeDimension::Surface,
+    };
+
+    let init_lifetime = SetAttributeModifier::new(Attribute::LIFETIME, writer.lit(0.4).expr());
+
+    let glow_velocity = writer.prop("glow_velocity").expr();
+    let init_velocity = SetAttributeModifier::new(Attribute::VELOCITY, glow_velocity);
+
+    let effect = EffectAsset::new(
+        400,
+        Spawner::rate(200.0.into()).with_starts_active(false),
+        writer.finish(),
+    )
+    .with_name("emit:reentry_glow")
+    .with_property("glow_velocity", Vec3::ZERO.into())
+    .init(init_position)
+    .init(init_velocity)
+    .init(init_lifetime)
+    .render(ColorOverLifetimeModifier {
+        gradient: color_gradient,
+    })
+    .render(SizeOverLifetimeModifier {
+        gradient: size_gradient,
+        screen_space_size: false,
+    })
+    .render(OrientModifier {
+        mode: OrientMode::ParallelCameraDepthPlane,
+    })
+    .render(ParticleTextureModifier {
+        texture: particle_texture,
+        sample_mapping: ImageSampleMapping::ModulateOpacityFromR,
+    });
+
+    glow_handle.handle = effects.add(effect);
+}
+
+/// Gives every entity with [Aerodynamics] a glow emitter, which stays off until it heats up.
+fn attach_reentry_glow(
+    mut commands: Commands,
+    glow: Res<ReentryGlowEffect>,
+    entities: Query<Entity, Added<Aerodynamics>>,
+) {
+    for entity in entities.iter() {
+        let emitter = commands
+            .spawn((
+                ParticleEffectBundle {
+                    effect: ParticleEffect::new(glow.handle.clone()),
+                    transform: Transform::IDENTITY,
+                    ..Default::default()
+                },
+                ReentryGlow {
+                    parent_entity: entity,
+                },
+            ))
+            .id();
+
+        commands.entity(entity).add_child(emitter);
+    }
+}
+
+/// Streams the glow backwards along the airflow while the [AtmosphericConditions] report enough heating.
+fn update_reentry_glow_velocity(
+    heated: Query<&AtmosphericConditions>,
+    mut query: Query<(
+        &mut CompiledParticleEffect,
+        &mut EffectSpawner,
+        &ReentryGlow,
+    )>,
+) {
+    for (mut compiled, mut spawner, glow) in query.iter_mut() {
+        let Ok(conditions) = heated.get(glow.parent_entity) else {
+            spawner.set_active(false);
+            continue;
+        };
+
+        if conditions.heating > GLOW_THRESHOLD {
+            let velocity = -conditions.airspeed.normalize_or_zero()
+                * (conditions.heating / GLOW_THRESHOLD)
+                    .log2()
+                    .clamp(1.0, 10.0);
+            compiled.set_property("glow_velocity", velocity.into());
+            spawner.set_active(true);
+        } else {
+            spawner.set_active(false);
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use bevy::{ecs::system::EntityCommands, prelude::*, reflect::TypePath, utils::HashSet};
+use bevy_common_assets::ron::RonAssetPlugin;
+use serde::Deserialize;
+
+use crate::{
+    atmosphere::Aerodynamics,
+    collision::GenerateConvexHull,
+    heat::{EngineHeat, HeatBundle, HeatCapacity, HeatTolerance, Radiators, Temperature},
+    impulse::{ShipBundle, ThrustCharacteristics},
+    physics::{Drag, Mass},
+    propellant::{PropellantTank, SpecificImpulse},
+    throttle::{Boost, SpoolCurve},
+    thruster::{Thruster, Thrusters},
+};
+
+/// A type of ship as described by a `*.ship.ron` asset. Spawn one with [spawn_ship].
+/// Editing the file while the game runs updates every ship of the class, except for its model.
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+pub struct ShipClass {
+    pub name: String,
+    /// Scene used as the ship's model, like `models/ship_small.glb#Scene0`.
+    pub model: String,
+    pub physics: ShipPhysics,
+    pub thrust: ShipThrust,
+    /// Ships without heat data never heat up.
+    #[serde(default)]
+    pub heat: Option<ShipHeat>,
+    #[serde(default)]
+    pub audio: ShipAudio,
+    #[serde(default)]
+    pub hardpoints: Vec<Hardpoint>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShipPhysics {
+    /// Mass of the ship without propellant, in kilograms.
+    pub mass: f32,
+    /// Constant [Drag] of ships without [Aerodynamics], which take their drag from the atmosphere instead.
+    #[serde(default)]
+    pub drag: f32,
+    /// Ships without it ignore atmospheres.
+    #[serde(default)]
+    pub aerodynamics: Option<Aerodynamics>,
+    /// Model nodes left out of the ship's collider, see [GenerateConvexHull::exclude].
+    #[serde(default)]
+    pub hull_exclude: Vec<String>,
+}
+
+/// Engine data, see [ThrustCharacteristics] for `min`, `max` and `rot`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShipThrust {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub rot: Vec3,
+    /// Ships without a curve respond instantly.
+    #[serde(default)]
+    pub spool: Option<SpoolCurve>,
+    #[serde(default)]
+    pub boost: Option<ShipBoost>,
+    /// Capacity of the ship's [PropellantTank], in kilograms. Ships without a tank never run dry.
+    #[serde(default)]
+    pub propellant: Option<f32>,
+    #[serde(default)]
+    pub specific_impulse: Option<f32>,
+}
+
+/// Settings of a ship's [Boost].
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ShipBoost {
+    pub multiplier: f32,
+    pub duration: f32,
+    pub cooldown: f32,
+}
+
+/// Thermal data, see [HeatBundle].
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct ShipHeat {
+    /// See [HeatCapacity].
+    pub capacity: f32,
+    /// See [EngineHeat].
+    pub engine_heat: f32,
+    pub radiators: Radiators,
+    #[serde(default)]
+    pub tolerance: HeatTolerance,
+}
+
+/// Sounds of a ship, as asset paths.
+#[derive(Debug, Clone, Deserialize, Component, Reflect)]
+#[serde(default)]
+pub struct ShipAudio {
+    /// Looped while the engines fire, louder with more thrust.
+    pub engine: String,
+    pub boost_start: String,
+    pub boost_end: String,
+}
+
+impl Default for ShipAudio {
+    fn default() -> Self {
+        Self {
+            engine: "audio/sci-fi-sounds/thrusterFire_002.ogg".to_string(),
+            boost_start: "audio/sci-fi-sounds/forceField_000.ogg".to_string(),
+            boost_end: "audio/sci-fi-sounds/forceField_003.ogg".to_string(),
+        }
+    }
+}
+
+/// Mounting point for equipment such as weapons, relative to the ship.
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct Hardpoint {
+    pub name: String,
+    pub position: Vec3,
+    /// Unit vector the mounted equipment faces. The ship's forward direction if omitted.
+    #[serde(default = "forward")]
+    pub direction: Vec3,
+}
+
+fn forward() -> Vec3 {
+    Vec3::NEG_Z
+}
+
+/// [Hardpoint]s of a ship spawned from a [ShipClass].
+#[derive(Debug, Default, Clone, Component, Reflect)]
+pub struct Hardpoints(pub Vec<Hardpoint>);
+
+/// Model scene of a ship spawned from a [ShipClass]. Added once the class has loaded and been applied.
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct ShipModel(pub Entity);
+
+pub struct ShipClassPlugin;
+
+impl Plugin for ShipClassPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<ShipClass>::new(&["ship.ron"]))
+            .add_systems(Update, ship_class_system)
+            .register_type::<ShipAudio>()
+            .register_type::<Hardpoints>()
+            .register_type::<ShipModel>();
+    }
+}
+
+/// Spawns a ship of `class` at `transform`. The ship starts out with a default [ShipBundle], which
+/// [ship_class_system] fills in from the class once it has loaded, so components inserted by the
+/// caller, like a starting [Velocity](crate::physics::Velocity), are kept.
+pub fn spawn_ship<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    class: Handle<ShipClass>,
+    transform: Transform,
+) -> EntityCommands<'w, 's, 'a> {
+    commands.spawn((
+        ShipBundle {
+            spatial: SpatialBundle::from_transform(transform),
+            ..Default::default()
+        },
+        class,
+    ))
+}
+
+/// Applies its [ShipClass] to every ship spawned by [spawn_ship] once the class has loaded, and again
+/// to every ship of a class whenever the class is modified. The propellant left in the tank, the
+/// state of the [Boost] and the ship's temperature carry over.
+#[allow(clippy::type_complexity)]
+pub fn ship_class_system(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<ShipClass>>,
+    classes: Res<Assets<ShipClass>>,
+    asset_server: Res<AssetServer>,
+    mut ships: Query<(
+        Entity,
+        &Handle<ShipClass>,
+        Has<ShipModel>,
+        Option<&mut PropellantTank>,
+        Option<&mut Boost>,
+        Option<&Thrusters>,
+        Has<Temperature>,
+    )>,
+    mut thrusters: Query<&mut Thruster>,
+) {
+    let modified: HashSet<AssetId<ShipClass>> = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+
+    for (ship, handle, spawned, tank, boost, owned, heated) in ships.iter_mut() {
+        if spawned && !modified.contains(&handle.id()) {
+            continue;
+        }
+        let Some(class) = classes.get(handle) else {
+            continue;
+        };
+
+        let model = (!spawned).then(|| {
+            commands
+                .spawn(SceneBundle {
+                    scene: asset_server.load(&class.model),
+                    ..Default::default()
+                })
+                .id()
+        });
+
+        let mut entity = commands.entity(ship);
+        if let Some(model) = model {
+            entity.add_child(model).insert((
+                ShipModel(model),
+                GenerateConvexHull {
+                    exclude: class.physics.hull_exclude.clone(),
+                },
+            ));
+        } else {
+            info!("reloading ship class '{}' for {:?}", class.name, ship);
+        }
+
+        // Stateful components are updated in place, so a reload doesn't refill the tank or reset the boost.
+        let propellant = match (class.thrust.propellant, tank) {
+            (Some(capacity), Some(mut tank)) => {
+                tank.capacity = capacity;
+                tank.mass = tank.mass.min(capacity);
+                tank.mass
+            }
+            (Some(capacity), None) => {
+                entity.insert(PropellantTank::full(capacity));
+                capacity
+            }
+            (None, tank) => {
+                if tank.is_some() {
+                    entity.remove::<PropellantTank>();
+                }
+                0.0
+            }
+        };
+
+        match (class.thrust.boost, boost) {
+            (Some(settings), Some(mut boost)) => {
+                boost.multiplier = settings.multiplier;
+                boost.duration = settings.duration;
+                boost.cooldown = settings.cooldown;
+            }
+            (Some(settings), None) => {
+                entity.insert(Boost {
+                    multiplier: settings.multiplier,
+                    duration: settings.duration,
+                    cooldown: settings.cooldown,
+                    ..Default::default()
+                });
+            }
+            (None, boost) => {
+                if boost.is_some() {
+                    entity.remove::<Boost>();
+                }
+            }
+        };
+
+        let characteristics = ThrustCharacteristics {
+            min: class.thrust.min,
+            max: class.thrust.max,
+            rot: class.thrust.rot,
+        };
+        for thruster in owned.iter().flat_map(|owned| owned.0.iter()) {
+            let Ok(direction) = thrusters.get(*thruster).map(|t| t.direction) else {
+                continue;
+            };
+            let sharing = owned
+                .iter()
+                .flat_map(|owned| owned.0.iter())
+                .filter(|other| {
+                    thrusters
+                        .get(**other)
+                        .is_ok_and(|t| t.direction == direction)
+                })
+                .count();
+            if let Ok(mut thruster) = thrusters.get_mut(*thruster) {
+                thruster.max_force = characteristics.available(direction) / sharing as f32;
+            }
+        }
+
+        entity.insert((
+            Mass(class.physics.mass + propellant),
+            Drag(if class.physics.aerodynamics.is_some() {
+                0.0
+            } else {
+                class.physics.drag
+            }),
+            characteristics,
+            class.audio.clone(),
+            Hardpoints(class.hardpoints.clone()),
+        ));
+
+        match class.physics.aerodynamics {
+            Some(aerodynamics) => entity.insert(aerodynamics),
+            None => entity.remove::<Aerodynamics>(),
+        };
+        match &class.thrust.spool {
+            Some(curve) => entity.insert(curve.clone()),
+            None => entity.remove::<SpoolCurve>(),
+        };
+        match class.thrust.specific_impulse {
+            Some(isp) => entity.insert(SpecificImpulse(isp)),
+            None => entity.remove::<SpecificImpulse>(),
+        };
+
+        match class.heat {
+            Some(heat) if heated => {
+                entity.insert((
+                    HeatCapacity(heat.capacity),
+                    EngineHeat(heat.engine_heat),
+                    heat.radiators,
+                    heat.tolerance,
+                ));
+            }
+            Some(heat) => {
+                entity.insert(HeatBundle {
+                    capacity: HeatCapacity(heat.capacity),
+                    engine_heat: EngineHeat(heat.engine_heat),
+                    radiators: heat.radiators,
+                    tolerance: heat.tolerance,
+                    ..Default::default()
+                });
+            }
+            None => {
+                entity.remove::<HeatBundle>();
+            }
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    collision::{contact_solver_system, rigid_body, BodyQuery, Collisions},
+    gravity::FeltGravity,
+    impulse::{AngularImpulse, Impulse},
+    physics::{
+        force_resolve_system, torque_resolve_system, Acceleration, AngularAcceleration,
+        AngularVelocity, Force, Inertia, Mass, OnRails, PhysicsSet, Torque, Velocity,
+    },
+    snapshot::ReflectSnapshot,
+};
+
+/// Marks a body which has come to rest. Sleeping bodies are skipped by [force_resolve_system],
+/// [torque_resolve_system], atmospheric drag, [velocity_system](crate::physics::velocity_system)
+/// and [angular_velocity_system](crate::physics::angular_velocity_system), so large numbers of them
+/// cost next to nothing. Added and removed by [sleep_system] and [wake_system].
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct Sleeping;
+
+/// Number of consecutive ticks the body has stayed under the [SleepSettings] thresholds.
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct RestingTicks(pub u32);
+
+/// Thresholds below which a body counts as resting. Velocities are relative to the body's parent,
+/// so a ship parked on a spinning station can fall asleep too.
+#[derive(Debug, Clone, Resource, Reflect)]
+pub struct SleepSettings {
+    /// In world units per second.
+    pub linear_velocity: f32,
+    /// In radians per second.
+    pub angular_velocity: f32,
+    /// In world units per second squared. Also the acceleration a [Force] must cause to wake a sleeping body.
+    pub linear_acceleration: f32,
+    /// In radians per second squared. Also the acceleration a [Torque] must cause to wake a sleeping body.
+    pub angular_acceleration: f32,
+    /// Number of ticks a whole island must rest before it falls asleep.
+    pub ticks: u32,
+}
+
+impl Default for SleepSettings {
+    fn default() -> Self {
+        Self {
+            linear_velocity: 0.05,
+            angular_velocity: 0.05,
+            linear_acceleration: 0.05,
+            angular_acceleration: 0.05,
+            // One second at the default 64Hz tick rate.
+            ticks: 64,
+        }
+    }
+}
+
+pub struct SleepPlugin;
+
+impl Plugin for SleepPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SleepSettings>()
+            .add_systems(
+                FixedUpdate,
+                // Bodies woken this tick must be resolved and integrated this tick.
+                (wake_system, apply_deferred)
+                    .chain()
+                    .in_set(PhysicsSet::Resolve)
+                    .before(force_resolve_system)
+                    .before(torque_resolve_system),
+            )
+            .add_systems(
+                FixedUpdate,
+                // Snapshots are captured right after the Solve set, and should include the markers.
+                (sleep_system, apply_deferred)
+                    .chain()
+                    .in_set(PhysicsSet::Solve)
+                    .after(contact_solver_system),
+            )
+            .register_type::<SleepSettings>()
+            .register_type::<Sleeping>()
+            .register_type::<RestingTicks>();
+    }
+}
+
+/// Wakes [Sleeping] bodies pushed by an [Impulse] or [AngularImpulse], or by a [Force] or [Torque]
+/// strong enough to exceed the [SleepSettings] acceleration thresholds. Weaker forces are discarded,
+/// since [force_resolve_system] and [torque_resolve_system] don't clear them for sleeping bodies.
+#[allow(clippy::type_complexity)]
+pub fn wake_system(
+    mut commands: Commands,
+    settings: Res<SleepSettings>,
+    mut sleepers: Query<
+        (
+            Entity,
+            &Transform,
+            &Mass,
+            Option<&Inertia>,
+            Option<&mut Force>,
+            Option<&mut Torque>,
+            Option<&Impulse>,
+            Option<&AngularImpulse>,
+            Option<&mut RestingTicks>,
+        ),
+        With<Sleeping>,
+    >,
+) {
+    for (entity, transform, mass, inertia, force, torque, impulse, angular_impulse, resting) in
+        sleepers.iter_mut()
+    {
+        let inertia = inertia
+            .copied()
+            .unwrap_or_default()
+            .world(transform.rotation);
+        let linear = force.as_ref().map(|f| f.0).unwrap_or(Vec3::ZERO);
+        let angular = match torque.as_ref() {
+            Some(torque) if inertia.determinant().is_normal() => inertia.inverse() * torque.0,
+            _ => Vec3::ZERO,
+        };
+
+        let pushed = impulse.is_some_and(|i| i.0 != Vec3::ZERO)
+            || angular_impulse.is_some_and(|i| i.0 != Vec3::ZERO)
+            || linear.length() > settings.linear_acceleration * mass.0
+            || angular.length() > settings.angular_acceleration;
+
+        if pushed {
+            commands.entity(entity).remove::<Sleeping>();
+            if let Some(mut resting) = resting {
+                resting.0 = 0;
+            }
+        } else {
+            if let Some(mut force) = force {
+                force.0 = Vec3::ZERO;
+            }
+            if let Some(mut torque) = torque {
+                torque.0 = Vec3::ZERO;
+            }
+        }
+    }
+}
+
+/// Counts the [RestingTicks] of every body with a [Mass] that isn't [OnRails], and puts bodies to
+/// sleep by island: bodies in contact with each other, directly or through other bodies, only fall
+/// asleep together once all of them have rested for [SleepSettings::ticks] ticks. Their velocities
+/// and accelerations are zeroed.
+///
+/// Bodies in free fall never rest, however weak their [FeltGravity]: only bodies touching something
+/// can rest in a gravity field.
+///
+/// A sleeping body wakes when its velocity exceeds the thresholds, for example because the
+/// [contact_solver_system] resolved a hit, when it feels gravity while touching nothing, or when a
+/// body in its island isn't resting.
+#[allow(clippy::type_complexity)]
+pub fn sleep_system(
+    mut commands: Commands,
+    settings: Res<SleepSettings>,
+    collisions: Res<Collisions>,
+    parents: Query<&Parent>,
+    bodies: BodyQuery,
+    mut dynamics: Query<
+        (
+            Entity,
+            Option<&mut Velocity>,
+            Option<&mut AngularVelocity>,
+            Option<&mut Acceleration>,
+            Option<&mut AngularAcceleration>,
+            Option<&mut RestingTicks>,
+            Has<Sleeping>,
+            Option<&FeltGravity>,
+        ),
+        (With<Mass>, Without<OnRails>),
+    >,
+) {
+    let mut states = HashMap::new();
+    let mut islands = HashMap::new();
+
+    let mut touching = HashSet::new();
+    for (a, b, _) in collisions.iter() {
+        touching.insert(rigid_body(a, &parents, &bodies));
+        touching.insert(rigid_body(b, &parents, &bodies));
+    }
+
+    for (
+        entity,
+        velocity,
+        angular_velocity,
+        acceleration,
+        angular_acceleration,
+        resting,
+        sleeping,
+        gravity,
+    ) in dynamics.iter_mut()
+    {
+        let falling = gravity.is_some_and(|g| g.0 != Vec3::ZERO) && !touching.contains(&entity);
+        let at_rest = !falling
+            && velocity.map(|v| v.length()).unwrap_or(0.0) < settings.linear_velocity
+            && angular_velocity.map(|v| v.length()).unwrap_or(0.0) < settings.angular_velocity
+            && acceleration.map(|a| a.length()).unwrap_or(0.0) < settings.linear_acceleration
+            && angular_acceleration.map(|a| a.length()).unwrap_or(0.0)
+                < settings.angular_acceleration;
+
+        let ticks = match resting {
+            Some(mut resting) => {
+                resting.0 = if at_rest {
+                    resting.0.saturating_add(1)
+                } else {
+                    0
+                };
+                resting.0
+            }
+            None => {
+                let ticks = u32::from(at_rest);
+                commands.entity(entity).insert(RestingTicks(ticks));
+                ticks
+            }
+        };
+
+        states.insert(entity, (ticks, sleeping));
+        islands.insert(entity, entity);
+    }
+
+    for (a, b, _) in collisions.iter() {
+        let a = rigid_body(a, &parents, &bodies);
+        let b = rigid_body(b, &parents, &bodies);
+        if islands.contains_key(&a) && islands.contains_key(&b) {
+            let (a, b) = (island_root(&mut islands, a), island_root(&mut islands, b));
+            islands.insert(a, b);
+        }
+    }
+
+    let mut members: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    for entity in states.keys() {
+        let root = island_root(&mut islands, *entity);
+        members.entry(root).or_default().push(*entity);
+    }
+
+    for island in members.values() {
+        let rested = island
+            .iter()
+            .all(|entity| states[entity].0 >= settings.ticks);
+
+        for entity in island.iter() {
+            let sleeping = states[entity].1;
+            let Ok((
+                _,
+                velocity,
+                angular_velocity,
+                acceleration,
+                angular_acceleration,
+                resting,
+                ..,
+            )) = dynamics.get_mut(*entity)
+            else {
+                continue;
+            };
+
+            if rested && !sleeping {
+                commands.entity(*entity).insert(Sleeping);
+                if let Some(mut velocity) = velocity {
+                    velocity.0 = Vec3::ZERO;
+                }
+                if let Some(mut angular_velocity) = angular_velocity {
+                    angular_velocity.0 = Vec3::ZERO;
+                }
+                if let Some(mut acceleration) = acceleration {
+                    acceleration.0 = Vec3::ZERO;
+                }
+                if let Some(mut angular_acceleration) = angular_acceleration {
+                    angular_acceleration.0 = Vec3::ZERO;
+                }
+            } else if !rested && sleeping {
+                commands.entity(*entity).remove::<Sleeping>();
+                if let Some(mut resting) = resting {
+                    resting.0 = 0;
+                }
+            }
+        }
+    }
+}
+
+/// Representative of the island `entity` belongs to, in the disjoint-set forest built by [sleep_system].
+fn island_root(islands: &mut HashMap<Entity, Entity>, entity: Entity) -> Entity {
+    let mut root = entity;
+    while islands[&root] != root {
+        root = islands[&root];
+    }
+
+    let mut current = entity;
+    while current != root {
+        current = islands.insert(current, root).unwrap_or(root);
+    }
+
+    root
+}
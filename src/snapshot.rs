@@ -0,0 +1,341 @@
+use std::{
+    any::{Any, TypeId},
+    collections::VecDeque,
+};
+
+use bevy::{prelude::*, reflect::FromType, time::Fixed};
+
+use crate::{
+    origin::FloatingOrigin,
+    physics::{AngularVelocity, PhysicsSet, SimulationTime, Velocity},
+};
+
+/// Values of one tracked component, one per entity of the [Snapshot] in the same order, or `None`
+/// for entities which didn't have it.
+type Column = Box<dyn Any + Send + Sync>;
+
+/// Entities captured in a [Snapshot], each with the parent its local [Transform] is relative to.
+type CapturedEntities = [(Entity, Option<Entity>)];
+
+/// Physics state of the world at the end of one tick.
+#[derive(Debug)]
+pub struct Snapshot {
+    /// [SimulationTime] after the tick was simulated.
+    pub time: SimulationTime,
+    /// Grid cell of the [FloatingOrigin] the captured root [Transform]s are relative to.
+    origin: Option<IVec3>,
+    /// Every captured entity, with the parent its local [Transform] is relative to.
+    entities: Vec<(Entity, Option<Entity>)>,
+    /// One [Column] per tracked component type.
+    columns: Vec<(TypeId, Column)>,
+}
+
+impl Snapshot {
+    pub fn tick(&self) -> u64 {
+        self.time.tick
+    }
+}
+
+/// Reflected type data which makes a component part of every [Snapshot]. Components opt in with
+/// `#[reflect(Component, Snapshot)]`, types from other crates with `App::register_type_data`.
+/// The [capture_snapshot_system] finds them in the [AppTypeRegistry], so no list of tracked
+/// components has to be kept anywhere.
+#[derive(Clone)]
+pub struct ReflectSnapshot {
+    capture: fn(&World, &CapturedEntities) -> Column,
+    restore: fn(&mut World, &CapturedEntities, &Column),
+}
+
+impl<T: Component + Clone> FromType<T> for ReflectSnapshot {
+    fn from_type() -> Self {
+        Self {
+            capture: capture_column::<T>,
+            restore: restore_column::<T>,
+        }
+    }
+}
+
+/// Copies component `T` of every entity into a [Column]. Stored as plain values rather than reflected
+/// ones, which keeps snapshots compact.
+fn capture_column<T: Component + Clone>(world: &World, entities: &CapturedEntities) -> Column {
+    let values: Vec<Option<T>> = entities
+        .iter()
+        .map(|(entity, _)| world.get::<T>(*entity).cloned())
+        .collect();
+    Box::new(values)
+}
+
+fn restore_column<T: Component + Clone>(
+    world: &mut World,
+    entities: &CapturedEntities,
+    column: &Column,
+) {
+    let Some(values) = column.downcast_ref::<Vec<Option<T>>>() else {
+        return;
+    };
+
+    for ((entity, _), value) in entities.iter().zip(values) {
+        let Some(mut entity_mut) = world.get_entity_mut(*entity) else {
+            continue;
+        };
+        match value {
+            Some(value) => {
+                entity_mut.insert(value.clone());
+            }
+            None => {
+                entity_mut.remove::<T>();
+            }
+        }
+    }
+}
+
+/// Lists the entities with some component, see [SnapshotAppExt::capture_entities_with].
+type EntityFilter = fn(&mut World) -> Vec<Entity>;
+
+/// Ring buffer holding a [Snapshot] of the most recent physics ticks, captured at the end of every
+/// `FixedUpdate` tick. Every component registered with [ReflectSnapshot] is tracked. Each snapshot
+/// stores every tracked component as one plain array of values, along with the hierarchy and
+/// [FloatingOrigin] cell the values are relative to.
+///
+/// Only entities which are simulated, i.e. have a [Velocity] or [AngularVelocity], are captured,
+/// along with those added by [SnapshotAppExt::capture_entities_with].
+#[derive(Resource)]
+pub struct SnapshotBuffer {
+    /// Maximum number of snapshots kept. Older ones are dropped first.
+    pub capacity: usize,
+    filters: Vec<EntityFilter>,
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl Default for SnapshotBuffer {
+    fn default() -> Self {
+        Self {
+            // Ten seconds at the default 64Hz tick rate.
+            capacity: 640,
+            filters: Vec::new(),
+            snapshots: VecDeque::new(),
+        }
+    }
+}
+
+impl SnapshotBuffer {
+    pub fn get(&self, tick: u64) -> Option<&Snapshot> {
+        let oldest = self.oldest_tick()?;
+        let snapshot = self.snapshots.get(tick.checked_sub(oldest)? as usize)?;
+        (snapshot.tick() == tick).then_some(snapshot)
+    }
+
+    pub fn oldest_tick(&self) -> Option<u64> {
+        self.snapshots.front().map(Snapshot::tick)
+    }
+
+    pub fn latest_tick(&self) -> Option<u64> {
+        self.snapshots.back().map(Snapshot::tick)
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    fn push(&mut self, snapshot: Snapshot) {
+        // A rollback resimulates ticks which may already be in the buffer.
+        while self
+            .snapshots
+            .back()
+            .is_some_and(|latest| latest.tick() >= snapshot.tick())
+        {
+            self.snapshots.pop_back();
+        }
+
+        self.snapshots.push_back(snapshot);
+        while self.snapshots.len() > self.capacity {
+            self.snapshots.pop_front();
+        }
+    }
+}
+
+pub trait SnapshotAppExt {
+    /// Captures entities with component `T` in every [Snapshot], even if they aren't simulated.
+    fn capture_entities_with<T: Component>(&mut self) -> &mut Self;
+}
+
+impl SnapshotAppExt for App {
+    fn capture_entities_with<T: Component>(&mut self) -> &mut Self {
+        self.init_resource::<SnapshotBuffer>()
+            .world
+            .resource_mut::<SnapshotBuffer>()
+            .filters
+            .push(|world| {
+                world
+                    .query_filtered::<Entity, With<T>>()
+                    .iter(world)
+                    .collect()
+            });
+        self
+    }
+}
+
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SnapshotBuffer>()
+            .capture_entities_with::<Velocity>()
+            .capture_entities_with::<AngularVelocity>()
+            .register_type::<Transform>()
+            .register_type_data::<Transform, ReflectSnapshot>()
+            // Systems early in the tick read the GlobalTransform propagated during the previous one.
+            .register_type::<GlobalTransform>()
+            .register_type_data::<GlobalTransform, ReflectSnapshot>()
+            .add_systems(
+                FixedUpdate,
+                capture_snapshot_system.after(PhysicsSet::Solve),
+            )
+            .add_systems(Update, rewind_system);
+    }
+}
+
+/// Records the state of all simulated entities, and others asked for, into the [SnapshotBuffer].
+pub fn capture_snapshot_system(world: &mut World) {
+    let filters = world.resource::<SnapshotBuffer>().filters.clone();
+    let mut captured: Vec<Entity> = filters.iter().flat_map(|filter| filter(world)).collect();
+    captured.sort();
+    captured.dedup();
+    let entities: Vec<(Entity, Option<Entity>)> = captured
+        .into_iter()
+        .map(|entity| (entity, world.get::<Parent>(entity).map(Parent::get)))
+        .collect();
+
+    let registry = world.resource::<AppTypeRegistry>().read();
+    let columns = registry
+        .iter()
+        .filter_map(|registration| {
+            let snapshot = registration.data::<ReflectSnapshot>()?;
+            Some((registration.type_id(), (snapshot.capture)(world, &entities)))
+        })
+        .collect();
+    drop(registry);
+
+    let snapshot = Snapshot {
+        time: world.resource::<SimulationTime>().clone(),
+        origin: world
+            .get_resource::<FloatingOrigin>()
+            .map(|origin| origin.cell),
+        entities,
+        columns,
+    };
+    world.resource_mut::<SnapshotBuffer>().push(snapshot);
+}
+
+/// Restores the world to the state it had at the end of `tick`, and discards all later snapshots.
+/// Tracked components which an entity didn't have back then are removed, and entities are moved
+/// back to the parent they had. Entities spawned since are left alone, and despawned ones aren't
+/// brought back.
+///
+/// If the [FloatingOrigin] has moved since, the restored root entities are shifted to be relative
+/// to where it is now.
+///
+/// Returns `false` if there is no snapshot for `tick`.
+pub fn rollback(world: &mut World, tick: u64) -> bool {
+    world.resource_scope(|world, mut buffer: Mut<SnapshotBuffer>| {
+        let Some(snapshot) = buffer.get(tick) else {
+            return false;
+        };
+
+        // Local transforms are relative to the parent, so restore the hierarchy first.
+        for (entity, parent) in snapshot.entities.iter() {
+            if world.get_entity(*entity).is_none() {
+                continue;
+            }
+            let current = world.get::<Parent>(*entity).map(Parent::get);
+            match parent {
+                Some(parent) if current != Some(*parent) && world.get_entity(*parent).is_some() => {
+                    world.entity_mut(*entity).set_parent(*parent);
+                }
+                None if current.is_some() => {
+                    world.entity_mut(*entity).remove_parent();
+                }
+                _ => {}
+            }
+        }
+
+        let restores: Vec<_> = {
+            let registry = world.resource::<AppTypeRegistry>().read();
+            snapshot
+                .columns
+                .iter()
+                .filter_map(|(type_id, column)| {
+                    let snapshot = registry.get_type_data::<ReflectSnapshot>(*type_id)?;
+                    Some((snapshot.restore, column))
+                })
+                .collect()
+        };
+        for (restore, column) in restores {
+            restore(world, &snapshot.entities, column);
+        }
+
+        let shift = match (snapshot.origin, world.get_resource::<FloatingOrigin>()) {
+            (Some(captured), Some(origin)) if captured != origin.cell => {
+                (captured - origin.cell).as_vec3() * origin.cell_size
+            }
+            _ => Vec3::ZERO,
+        };
+        if shift != Vec3::ZERO {
+            for (entity, _) in snapshot.entities.iter() {
+                let Some(mut entity_mut) = world.get_entity_mut(*entity) else {
+                    continue;
+                };
+                if !entity_mut.contains::<Parent>() {
+                    if let Some(mut transform) = entity_mut.get_mut::<Transform>() {
+                        transform.translation += shift;
+                    }
+                }
+                if let Some(mut global) = entity_mut.get_mut::<GlobalTransform>() {
+                    *global = Transform::from_translation(shift) * *global;
+                }
+            }
+        }
+
+        *world.resource_mut::<SimulationTime>() = snapshot.time.clone();
+        while buffer.latest_tick().is_some_and(|latest| latest > tick) {
+            buffer.snapshots.pop_back();
+        }
+
+        true
+    })
+}
+
+/// Runs the `FixedUpdate` schedule `ticks` times, as if that many fixed timesteps had passed.
+/// Used after a [rollback] to bring the world back to the present, for example with corrected inputs.
+pub fn resimulate(world: &mut World, ticks: u64) {
+    let original = *world.resource::<Time>();
+    let timestep = world.resource::<Time<Fixed>>().timestep();
+    let mut time = world.resource::<Time<Fixed>>().as_generic();
+
+    for _ in 0..ticks {
+        time.advance_by(timestep);
+        *world.resource_mut::<Time>() = time;
+        world.run_schedule(FixedUpdate);
+    }
+
+    *world.resource_mut::<Time>() = original;
+}
+
+/// Debugging aid: pressing F9 rewinds the simulation to the oldest snapshot in the buffer.
+pub fn rewind_system(world: &mut World) {
+    if !world
+        .get_resource::<Input<KeyCode>>()
+        .is_some_and(|keys| keys.just_pressed(KeyCode::F9))
+    {
+        return;
+    }
+
+    if let Some(tick) = world.resource::<SnapshotBuffer>().oldest_tick() {
+        info!("rewinding the simulation to tick {}", tick);
+        rollback(world, tick);
+    }
+}
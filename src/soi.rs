@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+
+use crate::{
+    gravity::{apply_gravity, point_acceleration, FeltGravity, GravitySettings, GravitySource},
+    kinematics::{world_motion, FrameQuery, Motion, ReparentInMotionExt},
+    local_system::CelestialBody,
+    orbit::{orbit_system, OrbitalElements},
+    physics::{
+        force_resolve_system, velocity_system, Acceleration, AngularVelocity, PhysicsSet, Velocity,
+    },
+    sleep::Sleeping,
+};
+
+/// Radius around a celestial body within which its gravity dominates that of its parent.
+/// Computed by [sphere_of_influence_system] from the body's orbit using the Laplace approximation
+/// `r = a * (mu / mu_parent)^(2/5)`. Bodies which don't orbit anything have an infinite sphere of influence.
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+pub struct SphereOfInfluence {
+    pub radius: f32,
+}
+
+impl Default for SphereOfInfluence {
+    fn default() -> Self {
+        Self {
+            radius: f32::INFINITY,
+        }
+    }
+}
+
+/// Opts an entity into patched-conic simulation: it is kept parented to the celestial body whose
+/// [SphereOfInfluence] it is currently in (its *primary*), and its [Transform] and [Velocity] are
+/// stored relative to that body. Only the primary's gravity acts on the entity.
+#[derive(Debug, Default, Component, Reflect)]
+pub struct SoiTracked;
+
+/// Sent whenever a [SoiTracked] entity moves from one primary to another.
+#[derive(Debug, Clone, Event)]
+pub struct SoiChanged {
+    pub entity: Entity,
+    /// The entity's previous primary, or `None` if it wasn't in any sphere of influence before.
+    pub previous: Option<Entity>,
+    pub current: Entity,
+}
+
+pub struct SoiPlugin;
+
+impl Plugin for SoiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SoiChanged>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    sphere_of_influence_system.before(PhysicsSet::Forces),
+                    primary_gravity_system
+                        .in_set(PhysicsSet::Resolve)
+                        .after(force_resolve_system),
+                    soi_transition_system
+                        .in_set(PhysicsSet::Integrate)
+                        .after(velocity_system)
+                        .after(orbit_system),
+                ),
+            )
+            .register_type::<SphereOfInfluence>()
+            .register_type::<SoiTracked>();
+    }
+}
+
+/// Computes the [SphereOfInfluence] of every [CelestialBody] from its orbit and its parent's [GravitySource].
+#[allow(clippy::type_complexity)]
+pub fn sphere_of_influence_system(
+    mut commands: Commands,
+    mut bodies: Query<
+        (
+            Entity,
+            &GravitySource,
+            Option<&OrbitalElements>,
+            Option<&Parent>,
+            Option<&mut SphereOfInfluence>,
+        ),
+        With<CelestialBody>,
+    >,
+    sources: Query<&GravitySource>,
+) {
+    for (entity, source, elements, parent, soi) in bodies.iter_mut() {
+        let parent_mu = parent.and_then(|p| sources.get(p.get()).ok()).map(|s| s.mu);
+
+        let radius = match (elements, parent_mu) {
+            (Some(elements), Some(parent_mu)) if parent_mu > 0.0 => {
+                elements.semi_major_axis * (source.mu / parent_mu).powf(0.4)
+            }
+            _ => f32::INFINITY,
+        };
+
+        if let Some(mut soi) = soi {
+            if soi.radius != radius {
+                soi.radius = radius;
+            }
+        } else {
+            commands.entity(entity).insert(SphereOfInfluence { radius });
+        }
+    }
+}
+
+/// Adds the gravity of each [SoiTracked] entity's primary to its [Acceleration].
+/// Since the entity's [Transform] is relative to the primary, this only depends on the local translation.
+/// [Sleeping] entities only get their [FeltGravity] updated.
+#[allow(clippy::type_complexity)]
+pub fn primary_gravity_system(
+    mut commands: Commands,
+    settings: Res<GravitySettings>,
+    sources: Query<&GravitySource>,
+    mut tracked: Query<
+        (
+            Entity,
+            &Transform,
+            &Parent,
+            &mut Acceleration,
+            Has<Sleeping>,
+            Option<&mut FeltGravity>,
+        ),
+        With<SoiTracked>,
+    >,
+) {
+    let softening_squared = settings.softening * settings.softening;
+    for (entity, transform, parent, mut acceleration, sleeping, felt) in tracked.iter_mut() {
+        let gravity = sources
+            .get(parent.get())
+            .map(|source| {
+                point_acceleration(
+                    Vec3::ZERO,
+                    source.mu,
+                    transform.translation,
+                    softening_squared,
+                )
+            })
+            .unwrap_or(Vec3::ZERO);
+        apply_gravity(
+            &mut commands,
+            entity,
+            gravity,
+            sleeping,
+            &mut acceleration,
+            felt,
+        );
+    }
+}
+
+/// Finds the innermost [SphereOfInfluence] containing each [SoiTracked] entity, and re-parents the
+/// entity to that body if it isn't already, with [set_parent_in_motion](ReparentInMotionExt::set_parent_in_motion)
+/// so its world-space position and motion are preserved.
+#[allow(clippy::type_complexity)]
+pub fn soi_transition_system(
+    mut commands: Commands,
+    mut events: EventWriter<SoiChanged>,
+    bodies: Query<(Entity, &SphereOfInfluence)>,
+    frames: FrameQuery<Without<SoiTracked>>,
+    tracked: Query<
+        (
+            Entity,
+            &Transform,
+            &Velocity,
+            Option<&AngularVelocity>,
+            Option<&Parent>,
+        ),
+        With<SoiTracked>,
+    >,
+) {
+    if bodies.is_empty() {
+        return;
+    }
+
+    let bodies: Vec<(Entity, f32, Motion)> = bodies
+        .iter()
+        .map(|(entity, soi)| (entity, soi.radius, world_motion(entity, &frames)))
+        .collect();
+
+    for (entity, transform, velocity, angular_velocity, parent) in tracked.iter() {
+        let previous = parent.map(|p| p.get());
+        let current_frame = previous
+            .map(|p| world_motion(p, &frames))
+            .unwrap_or(Motion::IDENTITY);
+
+        let motion = current_frame.transform_motion(&Motion {
+            transform: *transform,
+            velocity: velocity.0,
+            angular_velocity: angular_velocity.map(|v| v.0).unwrap_or(Vec3::ZERO),
+        });
+        let position = motion.transform.translation;
+
+        let innermost = bodies
+            .iter()
+            .filter(|(_, radius, frame)| frame.transform.translation.distance(position) < *radius)
+            .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b));
+
+        let Some((primary, ..)) = innermost else {
+            continue;
+        };
+
+        if Some(*primary) == previous {
+            continue;
+        }
+
+        debug!(
+            "{:?} entered the sphere of influence of {:?}",
+            entity, primary
+        );
+
+        commands.entity(entity).set_parent_in_motion(*primary);
+
+        events.send(SoiChanged {
+            entity,
+            previous,
+            current: *primary,
+        });
+    }
+}
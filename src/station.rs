@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::physics::AngularVelocity;
+use crate::{collision::GenerateConvexHull, physics::AngularVelocity};
 
 type SourceDirection = u8;
 
@@ -49,16 +49,20 @@ where
     }
 }
 
+/// Spawns a single pipe segment. Each segment gets its own convex hull, since the station as a whole is far from convex.
 fn quick_build(parent: &mut ChildBuilder, model: Handle<Scene>, offset: Vec3, rotation: u8) {
     parent
-        .spawn_bundle(SpatialBundle {
-            transform: Transform::from_translation(offset).with_rotation(Quat::from_rotation_y(
-                std::f32::consts::FRAC_PI_2 * rotation as f32,
-            )),
-            ..Default::default()
-        })
+        .spawn((
+            SpatialBundle {
+                transform: Transform::from_translation(offset).with_rotation(
+                    Quat::from_rotation_y(std::f32::consts::FRAC_PI_2 * rotation as f32),
+                ),
+                ..Default::default()
+            },
+            GenerateConvexHull::default(),
+        ))
         .with_children(|segment| {
-            segment.spawn_bundle(SceneBundle {
+            segment.spawn(SceneBundle {
                 scene: model.clone(),
                 ..Default::default()
             });
@@ -271,9 +275,11 @@ where
     }
 }
 
+/// [Bundle](https://erasin.wang/books/bevy-cheatbook/programming/ec.html#component-bundles) for stations.
+/// Stations are kinematic: they have no [Mass](crate::physics::Mass), so collisions never push them around,
+/// but ships touching them are carried along by their [AngularVelocity].
 #[derive(Bundle, Default)]
 pub struct StationBundle {
     pub angular_velocity: AngularVelocity,
-    #[bundle]
     pub spatial: SpatialBundle,
 }
@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    collision::{Collider, CollisionPlugin},
+    determinism::{DeterminismPlugin, PhysicsChecksum, SimulationRng},
+    gravity::{GravityPlugin, GravitySource},
+    impulse::{ImpulsePlugin, ShipBundle},
+    physics::{Integrator, PhysicsPlugin},
+    snapshot::resimulate,
+    tracking::{
+        AccelerateToInterceptTarget, PointInDirectionOfAcceleration, TargetEntity, TrackingPlugin,
+    },
+};
+
+/// Headless app with a few ships scattered by the [SimulationRng] of `seed`, intercepting a planet.
+fn scene(seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_plugins((
+        TransformPlugin,
+        HierarchyPlugin,
+        PhysicsPlugin,
+        GravityPlugin,
+        CollisionPlugin,
+        ImpulsePlugin,
+        TrackingPlugin,
+        DeterminismPlugin {
+            seed,
+            report_ambiguities: false,
+        },
+    ));
+    app.init_resource::<Assets<Mesh>>();
+    app.insert_resource(Integrator::RungeKutta4);
+
+    let target = app
+        .world
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(10.0, 0.0, 0.0)),
+            GravitySource { mu: 5.0 },
+            Collider::Sphere { radius: 1.0 },
+        ))
+        .id();
+
+    let rng = app.world.resource::<SimulationRng>().clone();
+    for i in 0..5 {
+        let mut rng = rng.rng(0, i);
+        let position = Vec3::new(rng.gen_range(-3.0..3.0), rng.gen_range(-3.0..3.0), i as f32);
+        app.world.spawn((
+            ShipBundle {
+                spatial: SpatialBundle::from_transform(Transform::from_translation(position)),
+                ..Default::default()
+            },
+            Collider::Sphere { radius: 0.5 },
+            TargetEntity(target),
+            AccelerateToInterceptTarget,
+            PointInDirectionOfAcceleration,
+        ));
+    }
+
+    app
+}
+
+/// [PhysicsChecksum] after each of `ticks` ticks.
+fn checksums(app: &mut App, ticks: usize) -> Vec<PhysicsChecksum> {
+    (0..ticks)
+        .map(|_| {
+            resimulate(&mut app.world, 1);
+            *app.world.resource::<PhysicsChecksum>()
+        })
+        .collect()
+}
+
+/// Two runs fed the same inputs produce the same checksums, tick for tick.
+#[test]
+fn same_inputs_same_checksums() {
+    let first = checksums(&mut scene(7), 200);
+    let second = checksums(&mut scene(7), 200);
+    assert_eq!(first, second);
+    assert_eq!(first.last().map(|checksum| checksum.tick), Some(200));
+
+    let other = checksums(&mut scene(8), 200);
+    assert_ne!(first, other);
+}
@@ -2,14 +2,15 @@ use bevy::{core_pipeline::tonemapping::Tonemapping, prelude::*};
 
 use crate::{
     camera::{TrackedByCamera, WorldCamera},
-    controls::PlayerControlled,
-    impulse::*,
-    physics::*,
+    controls::{FlightAssist, PlayerControlled},
+    interpolation::InterpolatedTransform,
+    ship_class::spawn_ship,
+    soi::SoiTracked,
+    trajectory::ShowTrajectory,
 };
 
 #[allow(dead_code)]
 pub fn spawn_player_ship(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let model = asset_server.load("models/ship_small_thrust.glb#Scene0");
     let camera = commands
         .spawn((
             Camera3dBundle {
@@ -23,37 +24,24 @@ pub fn spawn_player_ship(mut commands: Commands, asset_server: Res<AssetServer>)
                 ..Default::default()
             },
             WorldCamera,
+            // Follows the ship in FixedUpdate, so it needs smoothing just like the ship does.
+            InterpolatedTransform::default(),
         ))
         .id();
 
-    commands
-        .spawn((
-            ShipBundle {
-                physics: PhysicsBundle {
-                    drag: Drag(0.1),
-                    ..Default::default()
-                },
-                spatial: SpatialBundle {
-                    transform: Transform::from_translation(Vec3::from_slice(&[0.0, -0.0, 0.0])),
-                    ..Default::default()
-                },
-                thrust_characteristics: ThrustCharacteristics {
-                    min: Vec3::from_slice(&[-1.0, -1.0, -5.0]),
-                    max: Vec3::from_slice(&[1.0, 1.0, 1.0]),
-                    rot: Vec3::from_slice(&[5.0, 5.0, 5.0]),
-                },
-                ..Default::default()
-            },
-            PlayerControlled,
-            TrackedByCamera {
-                camera,
-                height: 5.0,
-            },
-        ))
-        .with_children(|parent| {
-            parent.spawn(SceneBundle {
-                scene: model.clone(),
-                ..Default::default()
-            });
-        });
+    spawn_ship(
+        &mut commands,
+        asset_server.load("ships/player.ship.ron"),
+        Transform::IDENTITY,
+    )
+    .insert((
+        PlayerControlled,
+        FlightAssist::default(),
+        SoiTracked,
+        TrackedByCamera {
+            camera,
+            height: 5.0,
+        },
+        ShowTrajectory::default(),
+    ));
 }
@@ -2,8 +2,9 @@
 pub mod camera;
 // mod planet;
 // mod route;
-// mod station;
+pub mod station;
 // mod thrust;
+pub mod system;
 pub mod tracking;
 
 /*
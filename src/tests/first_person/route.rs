@@ -3,9 +3,11 @@ use rand::Rng;
 
 use crate::{
     debug::DebugVector,
+    determinism::SimulationRng,
     impulse::*,
     physics::*,
     route::{Route, Waypoint},
+    ship_class::spawn_ship,
     tracking::{AccelerateToInterceptTarget, PointInDirectionOfAcceleration},
 };
 
@@ -13,79 +15,42 @@ use crate::{
 pub fn spawn_route_ship(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
+    simulation_rng: &SimulationRng,
+    time: &SimulationTime,
     mut waypoints: Vec<Waypoint>,
 ) {
-    let model = asset_server.load("models/ship_small.glb#Scene0");
+    let class = asset_server.load("ships/drone.ship.ron");
 
-    let id = {
-        // Leader
-        commands
-            .spawn_bundle(ShipBundle {
-                thrust_characteristics: ThrustCharacteristics {
-                    min: Vec3::from_slice(&[-1.0, -2.0, -1.0]),
-                    max: Vec3::from_slice(&[1.0, 2.0, 1.0]),
-                    rot: Vec3::from_slice(&[0.01, 0.01, 0.01]),
-                },
-                physics: PhysicsBundle {
-                    velocity: Velocity(Vec3::from_slice(&[0.0, 0.0, -1.0])),
-                    ..Default::default()
-                },
-                spatial: SpatialBundle {
-                    transform: Transform::from_xyz(0.0, 5.0, 50.0),
-                    ..Default::default()
-                },
-                ..Default::default()
-            })
-            //.debug_vector::<Acceleration>(asset_server)
-            .with_children(|parent| {
-                parent.spawn_bundle(SceneBundle {
-                    scene: model.clone(),
-                    ..Default::default()
-                });
-                parent.spawn_bundle((DebugVector::<Acceleration>::default(),));
-            })
-            .id()
-    };
+    // Leader
+    let id = spawn_ship(commands, class, Transform::from_xyz(0.0, 5.0, 50.0))
+        .insert(Velocity(Vec3::from_slice(&[0.0, 0.0, -1.0])))
+        //.debug_vector::<Acceleration>(asset_server)
+        .with_children(|parent| {
+            parent.spawn_bundle((DebugVector::<Acceleration>::default(),));
+        })
+        .id();
 
     waypoints.push(id.into());
-    let mut rng = rand::thread_rng();
+    let mut rng = simulation_rng.rng(time.tick, id.to_bits());
+    let class = asset_server.load("ships/courier.ship.ron");
 
     for i in 0..100 {
         let mut route = Route::from(waypoints.clone());
         route.set_waypoint(i);
 
-        commands
-            .spawn_bundle(ShipBundle {
-                impulse: Impulse(Vec3::from_slice(&[0.0, 0.0, -0.5])),
-                thrust_characteristics: ThrustCharacteristics {
-                    min: Vec3::from_slice(&[-0.1, -0.1, -1.0]),
-                    max: Vec3::from_slice(&[0.1, 0.1, 1.0]),
-                    rot: Vec3::from_slice(&[10.0, 10.0, 10.0]),
-                },
-                spatial: SpatialBundle {
-                    transform: Transform::from_xyz(
-                        rng.gen_range(-50.0..50.0),
-                        rng.gen_range(-50.0..50.0),
-                        rng.gen_range(-50.0..50.0),
-                    )
-                    .with_rotation(Quat::from_euler(
-                        EulerRot::XYZ,
-                        0.0,
-                        1.0,
-                        0.0,
-                    )),
-                    ..Default::default()
-                },
-                ..Default::default()
-            })
-            .with_children(|parent| {
-                parent.spawn_bundle(SceneBundle {
-                    scene: model.clone(),
-                    ..Default::default()
-                });
-            })
-            .insert(route)
-            .insert(PointInDirectionOfAcceleration)
-            .insert(AccelerateToInterceptTarget);
+        spawn_ship(
+            commands,
+            class.clone(),
+            Transform::from_xyz(
+                rng.gen_range(-50.0..50.0),
+                rng.gen_range(-50.0..50.0),
+                rng.gen_range(-50.0..50.0),
+            )
+            .with_rotation(Quat::from_euler(EulerRot::XYZ, 0.0, 1.0, 0.0)),
+        )
+        .insert(Impulse(Vec3::from_slice(&[0.0, 0.0, -0.5])))
+        .insert(route)
+        .insert(PointInDirectionOfAcceleration)
+        .insert(AccelerateToInterceptTarget);
     }
 }
@@ -32,6 +32,15 @@ fn donut() -> impl StationPart {
     }
 }
 
+pub fn spawn_stations(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_station(
+        &mut commands,
+        &asset_server,
+        Vec3::from_slice(&[0.0, 0.0, -30.0]),
+        0.1,
+    );
+}
+
 #[allow(dead_code)]
 pub fn spawn_station(
     commands: &mut Commands,
@@ -44,7 +53,7 @@ pub fn spawn_station(
     let rot = Quat::from_rotation_x(-rotspeed * 1.5);
 
     commands
-        .spawn_bundle(StationBundle {
+        .spawn(StationBundle {
             spatial: SpatialBundle {
                 transform: Transform::from_translation(position).with_rotation(rot),
                 ..Default::default()
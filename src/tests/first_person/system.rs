@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+
+use crate::local_system::LocalSystem;
+
+#[allow(dead_code)]
+pub fn spawn_solar_system(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let system: Handle<LocalSystem> = asset_server.load("systems/solar.system.ron");
+
+    commands.spawn((
+        Name::new("Solar System"),
+        system,
+        SpatialBundle::from_transform(Transform::from_xyz(0.0, -20.0, -150.0)),
+    ));
+}
@@ -1,2 +1,8 @@
+#[cfg(test)]
+mod determinism;
 pub mod first_person;
+#[cfg(test)]
+mod snapshot;
+#[cfg(test)]
+mod spatial_query;
 //pub mod strategy;
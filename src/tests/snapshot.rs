@@ -0,0 +1,84 @@
+use bevy::prelude::*;
+
+use crate::{
+    collision::{Collider, CollisionPlugin},
+    gravity::{GravityPlugin, GravitySource},
+    impulse::{Impulse, ImpulsePlugin, ShipBundle},
+    physics::{AngularVelocity, PhysicsBundle, PhysicsPlugin, SimulationTime, Velocity},
+    snapshot::{resimulate, rollback, SnapshotPlugin},
+};
+
+/// Position and motion of `entities`, compared bit for bit.
+fn state(app: &mut App, entities: &[Entity]) -> Vec<(Transform, Vec3, Vec3)> {
+    entities
+        .iter()
+        .map(|entity| {
+            let entity = app.world.entity(*entity);
+            (
+                *entity.get::<Transform>().unwrap(),
+                entity.get::<Velocity>().unwrap().0,
+                entity.get::<AngularVelocity>().unwrap().0,
+            )
+        })
+        .collect()
+}
+
+/// Rolling back and resimulating the same ticks ends in exactly the same state.
+#[test]
+fn rollback_and_resimulate_round_trip() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_plugins((
+        TransformPlugin,
+        HierarchyPlugin,
+        PhysicsPlugin,
+        GravityPlugin,
+        CollisionPlugin,
+        ImpulsePlugin,
+        SnapshotPlugin,
+    ));
+    app.init_resource::<Assets<Mesh>>();
+
+    app.world.spawn((
+        GravitySource { mu: 20.0 },
+        Collider::Sphere { radius: 2.0 },
+        TransformBundle::default(),
+    ));
+    let ship = app
+        .world
+        .spawn((
+            ShipBundle {
+                impulse: Impulse(Vec3::Y),
+                physics: PhysicsBundle {
+                    velocity: Velocity(Vec3::Z * 3.0),
+                    ..Default::default()
+                },
+                spatial: SpatialBundle::from_transform(Transform::from_xyz(6.0, 0.0, 0.0)),
+                ..Default::default()
+            },
+            Collider::Sphere { radius: 0.5 },
+        ))
+        .id();
+    let rock = app
+        .world
+        .spawn((
+            PhysicsBundle {
+                velocity: Velocity(Vec3::NEG_X),
+                ..Default::default()
+            },
+            Collider::Sphere { radius: 0.5 },
+            TransformBundle::from_transform(Transform::from_xyz(4.0, 0.0, 0.0)),
+        ))
+        .id();
+    let bodies = [ship, rock];
+
+    resimulate(&mut app.world, 120);
+    let end = state(&mut app, &bodies);
+    let tick = app.world.resource::<SimulationTime>().tick;
+
+    assert!(rollback(&mut app.world, tick - 80));
+    assert_ne!(state(&mut app, &bodies), end);
+    resimulate(&mut app.world, 80);
+
+    assert_eq!(app.world.resource::<SimulationTime>().tick, tick);
+    assert_eq!(state(&mut app, &bodies), end);
+}
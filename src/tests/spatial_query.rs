@@ -0,0 +1,88 @@
+use bevy::{ecs::system::SystemState, prelude::*};
+
+use crate::{
+    collision::{Collider, SpatialQuery, SpatialQueryFilter},
+    trigger::TriggerLayers,
+};
+
+/// Runs the [SpatialQuery] against colliders in a headless app, without a renderer.
+#[test]
+fn casts_and_overlaps_headless() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins((TransformPlugin, HierarchyPlugin));
+
+    let sphere = app
+        .world
+        .spawn((
+            Collider::Sphere { radius: 1.0 },
+            TransformBundle::from_transform(Transform::from_xyz(10.0, 0.0, 0.0)),
+        ))
+        .id();
+    let cube = app
+        .world
+        .spawn((
+            Collider::Cuboid {
+                half_extents: Vec3::ONE,
+            },
+            TriggerLayers(2),
+            TransformBundle::from_transform(Transform::from_xyz(0.0, 5.0, 0.0)),
+        ))
+        .id();
+
+    // Propagates the GlobalTransforms the queries work on.
+    app.update();
+
+    let mut state: SystemState<SpatialQuery> = SystemState::new(&mut app.world);
+    let query = state.get(&app.world);
+    let all = SpatialQueryFilter::default();
+
+    let hit = query.cast_ray(Vec3::ZERO, Vec3::X, 100.0, &all).unwrap();
+    assert_eq!(hit.entity, sphere);
+    assert!((hit.distance - 9.0).abs() < 1e-3, "{:?}", hit);
+    assert!(
+        hit.point.distance(Vec3::new(9.0, 0.0, 0.0)) < 1e-3,
+        "{:?}",
+        hit
+    );
+    assert!(hit.normal.distance(Vec3::NEG_X) < 1e-3, "{:?}", hit);
+    assert!(query.cast_ray(Vec3::ZERO, Vec3::X, 5.0, &all).is_none());
+
+    let hit = query
+        .cast_sphere(Vec3::ZERO, 0.5, Vec3::X, 100.0, &all)
+        .unwrap();
+    assert_eq!(hit.entity, sphere);
+    assert!((hit.distance - 8.5).abs() < 1e-3, "{:?}", hit);
+    assert!(
+        hit.point.distance(Vec3::new(9.0, 0.0, 0.0)) < 1e-3,
+        "{:?}",
+        hit
+    );
+    assert!(hit.normal.distance(Vec3::NEG_X) < 1e-3, "{:?}", hit);
+
+    let layered = SpatialQueryFilter {
+        mask: Some(2),
+        ..Default::default()
+    };
+    let hit = query
+        .cast_ray(Vec3::ZERO, Vec3::Y, 100.0, &layered)
+        .unwrap();
+    assert_eq!(hit.entity, cube);
+    assert!((hit.distance - 4.0).abs() < 1e-3, "{:?}", hit);
+    assert!(
+        hit.point.distance(Vec3::new(0.0, 4.0, 0.0)) < 1e-3,
+        "{:?}",
+        hit
+    );
+    assert!(hit.normal.distance(Vec3::NEG_Y) < 1e-3, "{:?}", hit);
+    assert!(query
+        .cast_ray(Vec3::ZERO, Vec3::X, 100.0, &layered)
+        .is_none());
+
+    assert_eq!(
+        query.overlap_sphere(Vec3::new(10.0, 2.0, 0.0), 1.5, &all),
+        vec![sphere]
+    );
+    assert_eq!(query.overlap_sphere(Vec3::ZERO, 5.0, &all), vec![cube]);
+    assert!(query.overlap_sphere(Vec3::ZERO, 1.0, &all).is_empty());
+}
@@ -0,0 +1,188 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    physics::{PhysicsSet, SimulationTime},
+    snapshot::ReflectSnapshot,
+};
+
+/// How fast an engine's output follows its throttle. Each curve lists `(level, rate)` points sorted by
+/// output level, where `rate` is how many fractions of full thrust per second the output changes by at
+/// that level. Rates between the points are interpolated linearly, and the outermost points extend to
+/// `0.0` and `1.0`. An engine without any points on a curve follows its throttle instantly.
+///
+/// Set per engine or per vessel, see [Thrusters](crate::thruster::Thrusters). Ships without a curve
+/// anywhere respond instantly.
+#[derive(Debug, Clone, Component, Reflect, Deserialize)]
+#[reflect(Component)]
+pub struct SpoolCurve {
+    /// Rates while the output rises towards the throttle. The rate at `0.0` must be positive,
+    /// or an idle engine never lights.
+    pub up: Vec<(f32, f32)>,
+    /// Rates while the output falls towards the throttle.
+    pub down: Vec<(f32, f32)>,
+}
+
+impl Default for SpoolCurve {
+    fn default() -> Self {
+        Self::linear(0.5, 0.25)
+    }
+}
+
+impl SpoolCurve {
+    /// An engine spooling from idle to full thrust in `up` seconds, and back down in `down` seconds.
+    pub fn linear(up: f32, down: f32) -> Self {
+        Self {
+            up: vec![(0.0, up.recip())],
+            down: vec![(0.0, down.recip())],
+        }
+    }
+
+    /// Output of an engine at `level` after following `throttle` for `delta` seconds.
+    pub fn step(&self, level: f32, throttle: f32, delta: f32) -> f32 {
+        let rising = throttle > level;
+        let rate = sample(if rising { &self.up } else { &self.down }, level);
+        if !rate.is_finite() {
+            return throttle;
+        }
+
+        if rising {
+            (level + rate * delta).min(throttle)
+        } else {
+            (level - rate * delta).max(throttle)
+        }
+    }
+}
+
+/// Rate of a [SpoolCurve] at `level`, or infinity if the curve has no points.
+fn sample(points: &[(f32, f32)], level: f32) -> f32 {
+    match points.iter().position(|(at, _)| *at > level) {
+        None => points
+            .last()
+            .map(|(_, rate)| *rate)
+            .unwrap_or(f32::INFINITY),
+        Some(0) => points[0].1,
+        Some(next) => {
+            let (a, rate_a) = points[next - 1];
+            let (b, rate_b) = points[next];
+            rate_a + (rate_b - rate_a) * (level - a) / (b - a)
+        }
+    }
+}
+
+/// Output of the six virtual engines of a ship, between `0.0` and `1.0`, in the order of
+/// [TRANSLATION_AXES](crate::impulse::TRANSLATION_AXES). Slots of directions the ship's thrusters
+/// cover stay at `0.0`, as those keep their output in [Thruster::level](crate::thruster::Thruster::level).
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct EngineThrottles(pub [f32; 6]);
+
+/// Afterburner which multiplies the thrust of a ship's engines for a limited time. Afterwards it has to
+/// cool down before it can be used again. Set [Boost::requested] to fire it.
+///
+/// The ship's thrust limits keep their base values: the [impulse_system](crate::impulse::impulse_system)
+/// applies the [Boost::factor] to its engines every tick.
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component, Snapshot)]
+pub struct Boost {
+    /// Factor the ship's thrust limits are raised by while boosting.
+    pub multiplier: f32,
+    /// How long a boost lasts, in seconds.
+    pub duration: f32,
+    /// Time after a boost until the next one can start, in seconds.
+    pub cooldown: f32,
+    /// Starts a boost if it's ready. Cleared by [boost_system] every tick, so requests made while
+    /// boosting or cooling down are dropped.
+    pub requested: bool,
+    /// Seconds left of the current boost, `0.0` while not boosting.
+    pub remaining: f32,
+    /// Seconds until the boost is ready again.
+    pub recharge: f32,
+}
+
+impl Default for Boost {
+    fn default() -> Self {
+        Self {
+            multiplier: 2.0,
+            duration: 3.0,
+            cooldown: 10.0,
+            requested: false,
+            remaining: 0.0,
+            recharge: 0.0,
+        }
+    }
+}
+
+impl Boost {
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0.0
+    }
+
+    pub fn is_ready(&self) -> bool {
+        !self.is_active() && self.recharge <= 0.0
+    }
+
+    /// Factor the ship's thrust is currently raised by, `1.0` while not boosting.
+    pub fn factor(&self) -> f32 {
+        if self.is_active() {
+            self.multiplier
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Sent in the tick a [Boost] starts.
+#[derive(Debug, Clone, Event)]
+pub struct BoostStarted {
+    pub ship: Entity,
+}
+
+/// Sent in the tick a [Boost] runs out.
+#[derive(Debug, Clone, Event)]
+pub struct BoostEnded {
+    pub ship: Entity,
+}
+
+pub struct ThrottlePlugin;
+
+impl Plugin for ThrottlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BoostStarted>()
+            .add_event::<BoostEnded>()
+            .add_systems(FixedUpdate, boost_system.in_set(PhysicsSet::Control))
+            .register_type::<SpoolCurve>()
+            .register_type::<Boost>()
+            .register_type::<EngineThrottles>();
+    }
+}
+
+/// Starts requested [Boost]s which are ready, and ends those which ran out. Sends [BoostStarted]
+/// and [BoostEnded] accordingly.
+pub fn boost_system(
+    time: Res<SimulationTime>,
+    mut started: EventWriter<BoostStarted>,
+    mut ended: EventWriter<BoostEnded>,
+    mut ships: Query<(Entity, &mut Boost)>,
+) {
+    for (ship, mut boost) in ships.iter_mut() {
+        let requested = boost.requested;
+        if requested {
+            boost.requested = false;
+        }
+        if boost.is_active() {
+            boost.remaining -= time.delta;
+            if !boost.is_active() {
+                boost.remaining = 0.0;
+                boost.recharge = boost.cooldown;
+                ended.send(BoostEnded { ship });
+            }
+        } else {
+            boost.recharge = (boost.recharge - time.delta).max(0.0);
+            if requested && boost.is_ready() {
+                boost.remaining = boost.duration;
+                started.send(BoostStarted { ship });
+            }
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use bevy::prelude::*;
 use bevy_kira_audio::{Audio, AudioControl, AudioInstance, AudioTween};
 
-use crate::physics::Acceleration;
+use crate::{
+    physics::{Acceleration, PhysicsSet},
+    ship_class::ShipAudio,
+    throttle::{BoostEnded, BoostStarted},
+};
 
 /// This is the exponent with which the maximum thrust is approached.
 /// 0.5 means approach target thrust at the square root of the difference
@@ -22,9 +26,40 @@ pub struct ThrustPlugin;
 
 impl Plugin for ThrustPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_systems(FixedUpdate, tag_thrusters_for_animation_system)
-            .add_systems(FixedUpdate, update_thrust_from_acceleration_system)
-            .add_systems(FixedUpdate, animate_thruster_system);
+        // Purely cosmetic, so these run after the physics tick has settled the ship's acceleration.
+        app.add_systems(
+            FixedUpdate,
+            (
+                tag_thrusters_for_animation_system,
+                update_thrust_from_acceleration_system,
+                animate_thruster_system,
+            )
+                .chain()
+                .after(PhysicsSet::Solve),
+        )
+        .add_systems(Update, boost_sound_system);
+    }
+}
+
+/// Plays a sound when the [Boost](crate::throttle::Boost) of a ship kicks in, and another when it
+/// runs out, as given by the ship's [ShipAudio].
+fn boost_sound_system(
+    mut started: EventReader<BoostStarted>,
+    mut ended: EventReader<BoostEnded>,
+    sounds: Query<&ShipAudio>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+) {
+    let defaults = ShipAudio::default();
+
+    for event in started.read() {
+        let sounds = sounds.get(event.ship).unwrap_or(&defaults);
+        audio.play(asset_server.load(&sounds.boost_start));
+    }
+
+    for event in ended.read() {
+        let sounds = sounds.get(event.ship).unwrap_or(&defaults);
+        audio.play(asset_server.load(&sounds.boost_end));
     }
 }
 
@@ -34,6 +69,7 @@ fn tag_thrusters_for_animation_system(
     mut commands: Commands,
     potential_thrusters: Query<(Entity, &Transform, &Name, &Parent), Added<Name>>,
     parents: Query<(Option<&Parent>, Option<&Acceleration>)>,
+    sounds: Query<&ShipAudio>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
 ) {
@@ -61,8 +97,12 @@ fn tag_thrusters_for_animation_system(
                     ship
                 );
 
+                let engine = sounds
+                    .get(ship)
+                    .map(|sounds| sounds.engine.clone())
+                    .unwrap_or_else(|_| ShipAudio::default().engine);
                 let sound = audio
-                    .play(asset_server.load("audio/sci-fi-sounds/thrusterFire_002.ogg"))
+                    .play(asset_server.load(engine))
                     .with_volume(0.0)
                     .looped()
                     .handle();
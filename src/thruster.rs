@@ -0,0 +1,201 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    impulse::{impulse_system, ThrustCharacteristics},
+    physics::{Acceleration, Force, PhysicsSet, Torque},
+    snapshot::{ReflectSnapshot, SnapshotAppExt},
+};
+
+/// An engine nozzle on a ship. Every tick, [thruster_force_system] pushes the [Thruster::vessel]
+/// along [Thruster::direction] with `max_force * boost * level * condition` newtons, applied at
+/// [Thruster::position]. Thrusters away from the center of mass therefore also turn the ship.
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Snapshot)]
+pub struct Thruster {
+    /// The simulated entity this thruster pushes.
+    pub vessel: Entity,
+    /// Force at full throttle, in newtons.
+    pub max_force: f32,
+    /// Where the force is applied, relative to the vessel's center of mass, in the vessel's local frame.
+    pub position: Vec3,
+    /// Unit vector along which the thruster pushes the vessel (opposite to its exhaust), in the vessel's local frame.
+    pub direction: Vec3,
+    /// Commanded output between `0.0` and `1.0`, set by the [impulse_system] every tick.
+    pub throttle: f32,
+    /// Output the thruster actually delivers, between `0.0` and `1.0`. Follows [Thruster::throttle]
+    /// along the [SpoolCurve](crate::throttle::SpoolCurve) of the thruster or its vessel, or instantly without one.
+    pub level: f32,
+    /// Fraction of [Thruster::max_force] the thruster can still deliver. `1.0` is undamaged, `0.0` is destroyed.
+    pub condition: f32,
+    /// [Boost::factor](crate::throttle::Boost::factor) of the vessel, set by the [impulse_system] every tick.
+    pub boost: f32,
+}
+
+impl Thruster {
+    /// Force the thruster currently exerts on its vessel, in newtons.
+    pub fn output(&self) -> f32 {
+        self.max_force * self.boost * self.level.clamp(0.0, 1.0) * self.condition.clamp(0.0, 1.0)
+    }
+}
+
+/// Lists the [Thruster]s of a vessel. Vessels with thrusters get their translational
+/// [Impulse](crate::impulse::Impulse) through them, as set by the [impulse_system].
+///
+/// Engine settings like [SpecificImpulse](crate::propellant::SpecificImpulse) and
+/// [SpoolCurve](crate::throttle::SpoolCurve) go on a [Thruster] entity to apply to that engine, or on
+/// the vessel to apply to all of its engines without their own. That includes its virtual engines
+/// along the directions it has no thrusters for.
+#[derive(Debug, Default, Clone, Component, Reflect)]
+pub struct Thrusters(pub Vec<Entity>);
+
+pub struct ThrusterPlugin;
+
+impl Plugin for ThrusterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, tag_thrusters_system)
+            .add_systems(
+                FixedUpdate,
+                thruster_force_system
+                    .after(impulse_system)
+                    .in_set(PhysicsSet::Forces),
+            )
+            .register_type::<Thrusters>()
+            // Thrusters aren't simulated themselves, but their spool state matters for resimulation.
+            .capture_entities_with::<Thruster>()
+            .register_type::<Thruster>();
+    }
+}
+
+/// Parses the direction a thruster pushes its vessel from a node name like `anim_thrust_z_neg`,
+/// which pushes the vessel along its local `-Z` axis. Suffixes Blender adds to duplicated nodes,
+/// like the `.001` of `anim_thrust_z_neg.001`, are ignored.
+pub fn thrust_direction(name: &str) -> Option<Vec3> {
+    let (_, suffix) = name.split_once("thrust_")?;
+    let suffix = suffix.split('.').next()?;
+    let mut parts = suffix.split('_');
+    let axis = match parts.next()? {
+        "x" => Vec3::X,
+        "y" => Vec3::Y,
+        "z" => Vec3::Z,
+        _ => return None,
+    };
+
+    match parts.next()? {
+        "pos" => Some(axis),
+        "neg" => Some(-axis),
+        _ => None,
+    }
+}
+
+/// Turns newly spawned nodes named like `anim_thrust_z_neg` into [Thruster]s of the closest
+/// simulated ancestor. The thrust available along each direction according to the vessel's
+/// [ThrustCharacteristics] is shared between the thrusters pointing that way.
+#[allow(clippy::type_complexity)]
+pub fn tag_thrusters_system(
+    mut commands: Commands,
+    candidates: Query<(Entity, &Name), Added<Name>>,
+    nodes: Query<(&Transform, Option<&Parent>, Option<&Acceleration>)>,
+    mut vessels: Query<(Option<&ThrustCharacteristics>, Option<&mut Thrusters>)>,
+) {
+    let found: HashMap<Entity, Vec3> = candidates
+        .iter()
+        .filter_map(|(entity, name)| Some((entity, thrust_direction(name.as_str())?)))
+        .collect();
+
+    let mut per_vessel: HashMap<Entity, Vec<(Entity, Vec3, Vec3)>> = HashMap::new();
+    for (&thruster, &direction) in found.iter() {
+        // Walk up to the vessel, accumulating the node's transform relative to it.
+        let mut transform = Transform::IDENTITY;
+        let mut current = thruster;
+        let mut vessel = None;
+        while let Ok((node_transform, parent, acceleration)) = nodes.get(current) {
+            if acceleration.is_some() {
+                vessel = Some(current);
+                break;
+            }
+            // A thruster nested in another thruster node belongs to it; only the outermost one counts.
+            if current != thruster && found.contains_key(&current) {
+                break;
+            }
+            transform = node_transform.mul_transform(transform);
+            let Some(parent) = parent else {
+                break;
+            };
+            current = parent.get();
+        }
+
+        if current != thruster && found.contains_key(&current) {
+            continue;
+        }
+
+        let Some(vessel) = vessel else {
+            warn!("found thruster {:?} with no simulated parent", thruster);
+            continue;
+        };
+
+        per_vessel
+            .entry(vessel)
+            .or_default()
+            .push((thruster, transform.translation, direction));
+    }
+
+    for (vessel, thrusters) in per_vessel {
+        let Ok((characteristics, existing)) = vessels.get_mut(vessel) else {
+            continue;
+        };
+
+        for &(thruster, position, direction) in thrusters.iter() {
+            let sharing = thrusters
+                .iter()
+                .filter(|(_, _, other)| *other == direction)
+                .count();
+            let available = characteristics
+                .map(|c| c.available(direction))
+                .unwrap_or(1.0);
+
+            debug!(
+                "{:?} is a thruster of {:?} pushing along {}",
+                thruster, vessel, direction
+            );
+            commands.entity(thruster).insert(Thruster {
+                vessel,
+                max_force: available / sharing as f32,
+                position,
+                direction,
+                throttle: 0.0,
+                level: 0.0,
+                condition: 1.0,
+                boost: 1.0,
+            });
+        }
+
+        let thrusters = thrusters.into_iter().map(|(thruster, ..)| thruster);
+        match existing {
+            Some(mut existing) => existing.0.extend(thrusters),
+            None => {
+                commands
+                    .entity(vessel)
+                    .insert(Thrusters(thrusters.collect()));
+            }
+        }
+    }
+}
+
+/// Adds the output of every [Thruster] to its vessel's [Force], and the moment around the
+/// vessel's center of mass to its [Torque].
+pub fn thruster_force_system(
+    thrusters: Query<&Thruster>,
+    mut vessels: Query<(&mut Force, &mut Torque, &Transform)>,
+) {
+    for thruster in thrusters.iter() {
+        let Ok((mut force, mut torque, transform)) = vessels.get_mut(thruster.vessel) else {
+            continue;
+        };
+
+        force.add_at_offset(
+            &mut torque,
+            transform.rotation * thruster.direction * thruster.output(),
+            transform.rotation * thruster.position,
+        );
+    }
+}
@@ -1,10 +1,15 @@
 use bevy::prelude::*;
 
 use crate::{
+    heat::ThermalStatus,
     impulse::{AngularImpulse, Impulse},
-    physics::{Acceleration, AngularVelocity, Velocity},
+    physics::{Acceleration, AngularVelocity, PhysicsSet, Velocity},
 };
 
+/// [ThermalStatus::heat_level] above which [accelerate_towards_target_system] eases off the engines,
+/// cutting them completely before the ship overheats.
+const HEAT_CAUTION: f32 = 0.8;
+
 /// This marker component enables the [rotate_to_face_acceleration_direction] system for this entity.
 #[derive(Component)]
 pub struct PointInDirectionOfAcceleration;
@@ -31,9 +36,18 @@ pub struct TrackingPlugin;
 
 impl Plugin for TrackingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(FixedUpdate, targeting_entity_system)
-            .add_systems(FixedUpdate, rotate_to_face_acceleration_direction_system)
-            .add_systems(FixedUpdate, accelerate_towards_target_system);
+        app.add_systems(
+            FixedUpdate,
+            (
+                targeting_entity_system,
+                (
+                    rotate_to_face_acceleration_direction_system,
+                    accelerate_towards_target_system,
+                ),
+            )
+                .chain()
+                .in_set(PhysicsSet::Control),
+        );
     }
 }
 
@@ -93,15 +107,26 @@ pub fn rotate_to_face_acceleration_direction_system(
 
 /// Perpetually to accelerate any entity with a [Target] component in such a way
 /// that it will arrive at the Target location... "soon".
+/// Ships running hot throttle back above [HEAT_CAUTION] to let their radiators catch up.
+#[allow(clippy::type_complexity)]
 pub fn accelerate_towards_target_system(
     mut query: Query<
-        (&mut Impulse, &Velocity, &Transform, &Target),
+        (
+            &mut Impulse,
+            &Velocity,
+            &Transform,
+            &Target,
+            Option<&ThermalStatus>,
+        ),
         With<AccelerateToInterceptTarget>,
     >,
 ) {
-    for (mut impulse, velocity, transform, target) in query.iter_mut() {
+    for (mut impulse, velocity, transform, target, thermal) in query.iter_mut() {
         // Poor man's integration. Bias slightly towards current velocity to give the approach a smooth curve
         let dir = target.0 - transform.translation - velocity.0 * 5.0;
-        impulse.0 = dir.normalize() * (dir.length() * 2.0).sqrt();
+        let restraint = thermal
+            .map(|t| ((1.0 - t.heat_level) / (1.0 - HEAT_CAUTION)).clamp(0.0, 1.0))
+            .unwrap_or(1.0);
+        impulse.0 = dir.normalize() * (dir.length() * 2.0).sqrt() * restraint;
     }
 }
@@ -0,0 +1,277 @@
+use bevy::{ecs::system::SystemParam, prelude::*, transform::TransformSystem};
+
+use crate::{
+    allocation::ThrustAllocation,
+    atmosphere::{air_at, drag_force, Aerodynamics, Atmosphere},
+    gravity::{point_acceleration, GravityField, GravitySettings, GravitySource, PointSource},
+    kinematics::{world_motion, FrameQuery, Motion},
+    local_system::CelestialBody,
+    physics::{Acceleration, Drag, Force, Integrator, KinematicState, Mass, OnRails, Velocity},
+    soi::SoiTracked,
+};
+
+/// Predicted motion of an entity, sampled at a fixed interval starting from its current state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory {
+    /// Entity whose frame the samples are expressed in, which is the predicted entity's parent
+    /// like for its [Transform] and [Velocity]. `None` if the entity has no parent.
+    pub frame: Option<Entity>,
+    /// Time between two samples, in seconds.
+    pub dt: f32,
+    /// Position and velocity at every sample. The first sample is the entity's current state.
+    pub samples: Vec<KinematicState>,
+}
+
+impl Trajectory {
+    /// Time from now until sample `index`, in seconds.
+    pub fn time(&self, index: usize) -> f32 {
+        self.dt * index as f32
+    }
+
+    /// Time from now until the trajectory first comes within `radius` of `point`, which must be
+    /// given in the trajectory's [Trajectory::frame]. Meant for collision look-ahead.
+    pub fn first_within(&self, point: Vec3, radius: f32) -> Option<f32> {
+        self.samples
+            .iter()
+            .position(|sample| sample.position.distance_squared(point) <= radius * radius)
+            .map(|index| self.time(index))
+    }
+}
+
+/// Draws the predicted [Trajectory] of the entity as a line fading out towards its end.
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct ShowTrajectory {
+    /// How far ahead to predict, in seconds.
+    pub horizon: f32,
+    /// Number of integration steps over the horizon.
+    pub steps: usize,
+    pub color: Color,
+}
+
+impl Default for ShowTrajectory {
+    fn default() -> Self {
+        Self {
+            horizon: 30.0,
+            steps: 300,
+            color: Color::CYAN,
+        }
+    }
+}
+
+pub struct TrajectoryPlugin;
+
+impl Plugin for TrajectoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            draw_trajectory_system.after(TransformSystem::TransformPropagate),
+        )
+        .register_type::<ShowTrajectory>();
+    }
+}
+
+/// Components of an entity needed to predict its motion.
+type PredictedBody = (
+    &'static Transform,
+    &'static Velocity,
+    Option<&'static Acceleration>,
+    Option<&'static Drag>,
+    Option<&'static Mass>,
+    Option<&'static Force>,
+    Option<&'static ThrustAllocation>,
+    Option<&'static Parent>,
+    Option<&'static Aerodynamics>,
+    Has<SoiTracked>,
+);
+
+/// Predicts where entities are going, by simulating them forward with the same [Integrator],
+/// [Drag], atmospheric [drag_force] and gravity as the physics systems. Usable from any system, for
+/// example by autopilots checking whether they are about to hit something.
+///
+/// Gravity sources and [Atmosphere]s are assumed to stay where they are over the prediction
+/// horizon, and [SoiTracked] entities are assumed to stay within their current primary's sphere of influence.
+#[derive(SystemParam)]
+pub struct TrajectoryPredictor<'w, 's> {
+    integrator: Res<'w, Integrator>,
+    gravity: Option<Res<'w, GravitySettings>>,
+    bodies: Query<'w, 's, PredictedBody, Without<OnRails>>,
+    sources: Query<'w, 's, (Entity, &'static GlobalTransform, &'static GravitySource)>,
+    frames: Query<'w, 's, &'static GlobalTransform>,
+    atmospheres: Query<'w, 's, (Entity, &'static Atmosphere, &'static CelestialBody)>,
+    motions: FrameQuery<'w, 's>,
+}
+
+impl<'w, 's> TrajectoryPredictor<'w, 's> {
+    /// Simulates `entity` forward for `horizon` seconds in `steps` steps, returning `steps + 1` samples.
+    /// With `hold_impulse`, the entity keeps applying the thrust of its latest [ThrustAllocation],
+    /// as if the current [Impulse](crate::impulse::Impulse) were held. Otherwise it coasts.
+    ///
+    /// Returns `None` if the entity doesn't exist, has no [Velocity], or is [OnRails].
+    pub fn predict_trajectory(
+        &self,
+        entity: Entity,
+        horizon: f32,
+        steps: usize,
+        hold_impulse: bool,
+    ) -> Option<Trajectory> {
+        let (
+            transform,
+            velocity,
+            acceleration,
+            drag,
+            mass,
+            force,
+            allocation,
+            parent,
+            aerodynamics,
+            soi_tracked,
+        ) = self.bodies.get(entity).ok()?;
+
+        let frame = parent.map(|p| p.get());
+        let drag = drag.map(|d| d.0).unwrap_or(0.0);
+        let dt = if steps > 0 {
+            horizon / steps as f32
+        } else {
+            0.0
+        };
+
+        // Entities without a Force keep whatever Acceleration they were given, just like in the physics tick.
+        let constant = match force {
+            None => acceleration.map(|a| a.0).unwrap_or(Vec3::ZERO),
+            Some(_) if hold_impulse => {
+                let mass = mass.map(|m| m.0).unwrap_or(1.0);
+                let thrust = allocation.map(|a| a.achieved_force).unwrap_or(Vec3::ZERO);
+                if mass > 0.0 {
+                    transform.rotation * thrust / mass
+                } else {
+                    Vec3::ZERO
+                }
+            }
+            Some(_) => Vec3::ZERO,
+        };
+
+        let frame_transform = frame
+            .and_then(|frame| self.frames.get(frame).ok())
+            .copied()
+            .unwrap_or_default();
+        let frame_rotation = frame_transform.to_scale_rotation_translation().1;
+
+        // Mirrors the gravity_system and primary_gravity_system, which don't run without GravitySettings.
+        let gravity = self.gravity.as_deref().map(|settings| {
+            let primary = frame
+                .filter(|_| soi_tracked)
+                .and_then(|frame| self.sources.get(frame).ok())
+                .map(|(_, _, source)| source.mu);
+            let field = (force.is_some() && !soi_tracked).then(|| {
+                GravityField::new(
+                    self.sources
+                        .iter()
+                        .map(|(entity, transform, source)| PointSource {
+                            entity,
+                            position: transform.translation(),
+                            mu: source.mu,
+                        })
+                        .collect(),
+                    settings,
+                )
+            });
+            (primary, field, settings.softening * settings.softening)
+        });
+
+        let gravity_at = |position: Vec3| match &gravity {
+            Some((Some(mu), _, softening_squared)) => {
+                point_acceleration(Vec3::ZERO, *mu, position, *softening_squared)
+            }
+            Some((None, Some(field), _)) => {
+                frame_rotation.inverse()
+                    * field.acceleration_at(frame_transform.transform_point(position), Some(entity))
+            }
+            _ => Vec3::ZERO,
+        };
+
+        // Mirrors the atmospheric_drag_system, which only acts on entities with a Force.
+        let atmospheres: Vec<_> = match aerodynamics.filter(|_| force.is_some()) {
+            Some(_) => self
+                .atmospheres
+                .iter()
+                .map(|(entity, atmosphere, body)| {
+                    (
+                        world_motion(entity, &self.motions),
+                        *atmosphere,
+                        body.radius,
+                    )
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        let frame_motion = frame
+            .map(|frame| world_motion(frame, &self.motions))
+            .unwrap_or(Motion::IDENTITY);
+        let mass = mass.map(|m| m.0).unwrap_or(1.0);
+
+        let air_drag_at = |sample: KinematicState| {
+            let Some(aerodynamics) = aerodynamics.filter(|_| !atmospheres.is_empty()) else {
+                return Vec3::ZERO;
+            };
+            let motion = frame_motion.transform_motion(&Motion {
+                transform: Transform::from_translation(sample.position),
+                velocity: sample.velocity,
+                angular_velocity: Vec3::ZERO,
+            });
+            let Some((density, airspeed)) = air_at(&motion, &atmospheres) else {
+                return Vec3::ZERO;
+            };
+            let force = drag_force(density, airspeed, aerodynamics.drag_area, mass, dt);
+            frame_motion.transform.rotation.inverse() * force / mass.max(f32::EPSILON)
+        };
+
+        let mut state = KinematicState::new(transform.translation, velocity.0);
+        let mut samples = Vec::with_capacity(steps + 1);
+        samples.push(state);
+        for _ in 0..steps {
+            state = self.integrator.step(state, drag, dt, |sample| {
+                constant + gravity_at(sample.position) + air_drag_at(sample)
+            });
+            samples.push(state);
+        }
+
+        Some(Trajectory { frame, dt, samples })
+    }
+}
+
+/// Draws the predicted path of every entity with [ShowTrajectory], assuming its current impulse is held.
+/// Runs after transform propagation, so the line starts where the entity is rendered.
+pub fn draw_trajectory_system(
+    mut gizmos: Gizmos,
+    predictor: TrajectoryPredictor,
+    shown: Query<(Entity, &ShowTrajectory)>,
+    frames: Query<&GlobalTransform>,
+) {
+    for (entity, show) in shown.iter() {
+        let Some(trajectory) = predictor.predict_trajectory(entity, show.horizon, show.steps, true)
+        else {
+            continue;
+        };
+
+        let frame = trajectory
+            .frame
+            .and_then(|frame| frames.get(frame).ok())
+            .copied()
+            .unwrap_or_default();
+        let last = trajectory.samples.len().saturating_sub(1).max(1) as f32;
+
+        gizmos.linestrip_gradient(
+            trajectory
+                .samples
+                .iter()
+                .enumerate()
+                .map(|(index, sample)| {
+                    (
+                        frame.transform_point(sample.position),
+                        show.color
+                            .with_a(show.color.a() * (1.0 - index as f32 / last)),
+                    )
+                }),
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{math::Affine3A, prelude::*, utils::HashSet};
+
+use crate::{
+    collision::{collision_detection_system, rigid_body, Aabb, BodyQuery},
+    physics::PhysicsSet,
+};
+
+/// Region of space sensing other entities, in the entity's local space.
+#[derive(Debug, Clone, Reflect)]
+pub enum TriggerShape {
+    Sphere {
+        radius: f32,
+    },
+    Cuboid {
+        half_extents: Vec3,
+    },
+    /// Everything within `range` of the entity's origin and at most `half_angle` radians away
+    /// from its forward direction, the local negative Z-axis.
+    Cone {
+        range: f32,
+        half_angle: f32,
+    },
+}
+
+impl TriggerShape {
+    /// Whether `point`, given in the shape's local space, lies inside the shape.
+    pub fn contains(&self, point: Vec3) -> bool {
+        match self {
+            TriggerShape::Sphere { radius } => point.length_squared() <= radius * radius,
+            TriggerShape::Cuboid { half_extents } => point.abs().cmple(*half_extents).all(),
+            TriggerShape::Cone { range, half_angle } => {
+                point.length_squared() <= range * range
+                    && (point == Vec3::ZERO || Vec3::NEG_Z.angle_between(point) <= *half_angle)
+            }
+        }
+    }
+
+    /// Corners of a box containing the shape, in the shape's local space.
+    fn local_bounds(&self) -> (Vec3, Vec3) {
+        match self {
+            TriggerShape::Sphere { radius } => (Vec3::splat(-radius), Vec3::splat(*radius)),
+            TriggerShape::Cuboid { half_extents } => (-*half_extents, *half_extents),
+            TriggerShape::Cone { range, half_angle } => {
+                let lateral = range * half_angle.min(FRAC_PI_2).sin();
+                let back = if *half_angle <= FRAC_PI_2 {
+                    0.0
+                } else {
+                    -range * half_angle.cos()
+                };
+                (
+                    Vec3::new(-lateral, -lateral, -range),
+                    Vec3::new(lateral, lateral, back),
+                )
+            }
+        }
+    }
+
+    /// World-space bounding box of the shape when placed at `transform`.
+    pub fn aabb(&self, transform: &Affine3A) -> Aabb {
+        let (min, max) = self.local_bounds();
+        (0..8)
+            .map(|corner| {
+                let select = BVec3::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0);
+                transform.transform_point3(Vec3::select(select, max, min))
+            })
+            .fold(
+                Aabb {
+                    min: Vec3::INFINITY,
+                    max: Vec3::NEG_INFINITY,
+                },
+                |aabb, point| Aabb {
+                    min: aabb.min.min(point),
+                    max: aabb.max.max(point),
+                },
+            )
+    }
+}
+
+/// Sends [TriggerEnter] and [TriggerExit] events as entities on any of the layers in `mask` move in
+/// and out of its [TriggerShape], for example to detect ships entering a docking zone.
+/// Entities belonging to the same rigid body as the trigger are ignored.
+#[derive(Debug, Clone, Component, Reflect)]
+pub struct TriggerVolume {
+    pub shape: TriggerShape,
+    /// Bit mask of the [TriggerLayers] this volume senses.
+    pub mask: u32,
+}
+
+/// Bit mask of the layers an entity is on. Trigger volumes sense an entity's origin if their
+/// [TriggerVolume::mask] shares a bit with it, and [SpatialQueryFilter](crate::collision::SpatialQueryFilter)s
+/// use the same layers to pick colliders. See [TriggerAppExt::add_trigger_layer] for putting
+/// every entity with some marker component on a layer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Component, Reflect)]
+pub struct TriggerLayers(pub u32);
+
+/// Sent in the tick `entity` enters the volume of `trigger`.
+#[derive(Debug, Clone, Event)]
+pub struct TriggerEnter {
+    pub trigger: Entity,
+    pub entity: Entity,
+}
+
+/// Sent in the tick `entity` leaves the volume of `trigger`, or either of them is despawned.
+#[derive(Debug, Clone, Event)]
+pub struct TriggerExit {
+    pub trigger: Entity,
+    pub entity: Entity,
+}
+
+/// Every entity currently inside a [TriggerVolume], keyed by `(trigger, entity)`.
+#[derive(Debug, Default, Resource)]
+pub struct TriggerOverlaps {
+    overlaps: HashSet<(Entity, Entity)>,
+}
+
+impl TriggerOverlaps {
+    pub fn contains(&self, trigger: Entity, entity: Entity) -> bool {
+        self.overlaps.contains(&(trigger, entity))
+    }
+
+    /// Every entity currently inside `trigger`.
+    pub fn inside(&self, trigger: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.overlaps
+            .iter()
+            .filter(move |(t, _)| *t == trigger)
+            .map(|(_, entity)| *entity)
+    }
+}
+
+/// Systems keeping [TriggerLayers] in sync with marker components, see [TriggerAppExt::add_trigger_layer].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct TriggerLayerSet;
+
+pub trait TriggerAppExt {
+    /// Puts every entity with component `T` on `layers`, and takes it off them again when `T` is removed.
+    /// Every entity without `T` is kept off `layers`, so they belong to `T` alone: don't share them with
+    /// other markers or set them by hand.
+    fn add_trigger_layer<T: Component>(&mut self, layers: u32) -> &mut Self;
+}
+
+impl TriggerAppExt for App {
+    fn add_trigger_layer<T: Component>(&mut self, layers: u32) -> &mut Self {
+        self.add_systems(
+            FixedUpdate,
+            trigger_layer_system::<T>(layers)
+                .in_set(TriggerLayerSet)
+                // Each of these only ever sets or clears its own bits.
+                .ambiguous_with(TriggerLayerSet),
+        )
+    }
+}
+
+pub struct TriggerPlugin;
+
+impl Plugin for TriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TriggerOverlaps>()
+            .add_event::<TriggerEnter>()
+            .add_event::<TriggerExit>()
+            .configure_sets(
+                FixedUpdate,
+                TriggerLayerSet
+                    .in_set(PhysicsSet::Collide)
+                    .before(trigger_system),
+            )
+            .add_systems(
+                FixedUpdate,
+                trigger_system
+                    .in_set(PhysicsSet::Collide)
+                    .after(collision_detection_system),
+            )
+            .register_type::<TriggerVolume>()
+            .register_type::<TriggerLayers>();
+    }
+}
+
+/// Adds `layers` to the [TriggerLayers] of every entity with component `T`, and removes them from
+/// every entity without it. Both run every tick rather than reacting to removals, which a `FixedUpdate`
+/// system can miss when a frame runs no tick.
+#[allow(clippy::type_complexity)]
+pub fn trigger_layer_system<T: Component>(
+    layers: u32,
+) -> impl FnMut(
+    Commands,
+    Query<(Entity, Option<&mut TriggerLayers>), With<T>>,
+    Query<&mut TriggerLayers, Without<T>>,
+) {
+    move |mut commands, mut marked, mut unmarked| {
+        for (entity, current) in marked.iter_mut() {
+            match current {
+                Some(mut current) => {
+                    if current.0 & layers != layers {
+                        current.0 |= layers;
+                    }
+                }
+                None => {
+                    commands.entity(entity).insert(TriggerLayers(layers));
+                }
+            }
+        }
+
+        for mut current in unmarked.iter_mut() {
+            if current.0 & layers != 0 {
+                current.0 &= !layers;
+            }
+        }
+    }
+}
+
+/// Tests the origin of every entity with [TriggerLayers] against every [TriggerVolume] sensing one of
+/// its layers, updates [TriggerOverlaps] and sends [TriggerEnter] and [TriggerExit] for pairs which changed.
+///
+/// Sensed entities are sorted along the X-axis, so each volume only tests those within the X range
+/// of its bounding box.
+pub fn trigger_system(
+    mut overlaps: ResMut<TriggerOverlaps>,
+    mut entered: EventWriter<TriggerEnter>,
+    mut exited: EventWriter<TriggerExit>,
+    triggers: Query<(Entity, &TriggerVolume, &GlobalTransform)>,
+    sensed: Query<(Entity, &TriggerLayers, &GlobalTransform)>,
+    parents: Query<&Parent>,
+    bodies: BodyQuery,
+) {
+    let mut points: Vec<(Entity, Vec3, u32)> = sensed
+        .iter()
+        .filter(|(_, layers, _)| layers.0 != 0)
+        .map(|(entity, layers, transform)| (entity, transform.translation(), layers.0))
+        .collect();
+    points.sort_unstable_by(|(_, a, _), (_, b, _)| a.x.total_cmp(&b.x));
+
+    let mut current = HashSet::new();
+    for (trigger, volume, transform) in triggers.iter() {
+        let affine = transform.affine();
+        let aabb = volume.shape.aabb(&affine);
+        let to_local = affine.inverse();
+        let body = rigid_body(trigger, &parents, &bodies);
+
+        let start = points.partition_point(|(_, position, _)| position.x < aabb.min.x);
+        for (entity, position, layers) in points[start..]
+            .iter()
+            .take_while(|(_, position, _)| position.x <= aabb.max.x)
+        {
+            if layers & volume.mask == 0
+                || !aabb.contains(*position)
+                || rigid_body(*entity, &parents, &bodies) == body
+            {
+                continue;
+            }
+
+            if volume.shape.contains(to_local.transform_point3(*position)) {
+                current.insert((trigger, *entity));
+            }
+        }
+    }
+
+    for &(trigger, entity) in current.iter() {
+        if !overlaps.overlaps.contains(&(trigger, entity)) {
+            entered.send(TriggerEnter { trigger, entity });
+        }
+    }
+
+    for &(trigger, entity) in overlaps.overlaps.iter() {
+        if !current.contains(&(trigger, entity)) {
+            exited.send(TriggerExit { trigger, entity });
+        }
+    }
+
+    overlaps.overlaps = current;
+}
@@ -0,0 +1,239 @@
+use std::time::Duration;
+
+use bevy::{app::RunFixedUpdateLoop, prelude::*, time::run_fixed_update_schedule};
+
+use crate::{
+    collision::{rigid_body, BodyQuery, Collider},
+    controls::PlayerControlled,
+    impulse::{AngularImpulse, Impulse},
+    soi::SphereOfInfluence,
+    trajectory::TrajectoryPredictor,
+};
+
+/// Multipliers the player steps through with [time_warp_input_system].
+pub const WARP_LEVELS: [f32; 8] = [1.0, 5.0, 10.0, 50.0, 100.0, 1000.0, 10000.0, 100000.0];
+
+/// Number of samples of the player's trajectory checked for hazards by [time_warp_limit_system].
+const LOOK_AHEAD_STEPS: usize = 256;
+
+/// Speeds up the simulation. Every fixed timestep then simulates `factor` times as much time,
+/// split over enough physics ticks that none covers more than [TimeWarp::max_tick] seconds, up to
+/// [TimeWarp::max_substeps] ticks. Past that, ticks get longer instead.
+/// Bodies driven by their orbit follow along, since they are positioned from the [SimulationTime](crate::physics::SimulationTime).
+#[derive(Debug, Clone, Resource, Reflect)]
+pub struct TimeWarp {
+    /// Multiplier asked for by the player, between `1.0` and `100000.0`.
+    pub requested: f32,
+    /// Multiplier in effect. At most [TimeWarp::requested], but lowered by [time_warp_limit_system]
+    /// while a collision or sphere of influence change is coming up.
+    pub factor: f32,
+    /// Longest stretch of simulated time a single physics tick should cover, in seconds.
+    pub max_tick: f64,
+    /// Most physics ticks run per fixed timestep, which bounds the cost of a frame. Past
+    /// `max_tick * max_substeps` simulated seconds per timestep, ticks get longer instead, so the
+    /// highest [WARP_LEVELS] stay reachable. Bodies [OnRails](crate::physics::OnRails) are positioned
+    /// analytically and don't mind, and the [time_warp_limit_system] lowers the warp before a long tick
+    /// could carry the player past an obstacle.
+    pub max_substeps: u32,
+    /// How many real seconds ahead collisions and sphere of influence changes are anticipated.
+    pub look_ahead: f32,
+    /// Elapsed [Time<Fixed>] when [time_warp_substep_system] last ran.
+    last_fixed_elapsed: Duration,
+}
+
+impl Default for TimeWarp {
+    fn default() -> Self {
+        Self {
+            requested: 1.0,
+            factor: 1.0,
+            max_tick: 1.0,
+            max_substeps: 16,
+            look_ahead: 5.0,
+            last_fixed_elapsed: Duration::ZERO,
+        }
+    }
+}
+
+impl TimeWarp {
+    /// Number of physics ticks run per fixed timestep of `timestep` seconds.
+    pub fn substeps(&self, timestep: f64) -> u32 {
+        ((self.factor as f64 * timestep / self.max_tick).ceil() as u32).clamp(1, self.max_substeps)
+    }
+
+    /// Simulated time covered by each physics tick, for a fixed timestep of `timestep` seconds.
+    pub fn tick_delta(&self, timestep: f64) -> f64 {
+        timestep * self.factor as f64 / self.substeps(timestep) as f64
+    }
+}
+
+pub struct TimeWarpPlugin;
+
+impl Plugin for TimeWarpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeWarp>()
+            .add_systems(
+                RunFixedUpdateLoop,
+                time_warp_substep_system.after(run_fixed_update_schedule),
+            )
+            .add_systems(
+                Update,
+                (time_warp_input_system, time_warp_limit_system).chain(),
+            )
+            .register_type::<TimeWarp>();
+    }
+}
+
+/// Runs the extra `FixedUpdate` ticks [TimeWarp::substeps] asks for, for every fixed timestep
+/// which passed this frame.
+pub fn time_warp_substep_system(world: &mut World) {
+    let fixed = world.resource::<Time<Fixed>>();
+    let (timestep, elapsed) = (fixed.timestep(), fixed.elapsed());
+
+    let mut warp = world.resource_mut::<TimeWarp>();
+    let timesteps =
+        elapsed.saturating_sub(warp.last_fixed_elapsed).as_nanos() / timestep.as_nanos().max(1);
+    warp.last_fixed_elapsed = elapsed;
+
+    let extra = timesteps as u32 * (warp.substeps(timestep.as_secs_f64()) - 1);
+    if extra == 0 {
+        return;
+    }
+
+    let _ = world.try_schedule_scope(FixedUpdate, |world, schedule| {
+        *world.resource_mut::<Time>() = world.resource::<Time<Fixed>>().as_generic();
+        for _ in 0..extra {
+            schedule.run(world);
+        }
+    });
+    *world.resource_mut::<Time>() = world.resource::<Time<Virtual>>().as_generic();
+}
+
+/// `.` speeds time up to the next of the [WARP_LEVELS], `,` slows it down to the previous one.
+pub fn time_warp_input_system(keys: Res<Input<KeyCode>>, mut warp: ResMut<TimeWarp>) {
+    let requested = if keys.just_pressed(KeyCode::Period) {
+        WARP_LEVELS
+            .into_iter()
+            .find(|level| *level > warp.requested)
+    } else if keys.just_pressed(KeyCode::Comma) {
+        WARP_LEVELS
+            .into_iter()
+            .rev()
+            .find(|level| *level < warp.requested)
+    } else {
+        None
+    };
+
+    if let Some(requested) = requested {
+        info!("time warp set to {}x", requested);
+        warp.requested = requested;
+    }
+}
+
+/// Keeps time warp safe for the [PlayerControlled] ship. Any thrust cancels the warp entirely.
+/// Otherwise the ship's coasting trajectory is predicted [TimeWarp::look_ahead] real seconds ahead,
+/// and the warp is lowered so that hitting a [Collider] or changing spheres of influence is always
+/// at least that far away.
+///
+/// Obstacles are approximated by bounding spheres at their current positions.
+#[allow(clippy::too_many_arguments)]
+pub fn time_warp_limit_system(
+    mut warp: ResMut<TimeWarp>,
+    predictor: TrajectoryPredictor,
+    player: Query<(Entity, &Impulse, &AngularImpulse), With<PlayerControlled>>,
+    colliders: Query<(Entity, &GlobalTransform, &Collider)>,
+    spheres: Query<(Entity, &GlobalTransform, &SphereOfInfluence)>,
+    frames: Query<&GlobalTransform>,
+    parents: Query<&Parent>,
+    bodies: BodyQuery,
+) {
+    warp.requested = warp
+        .requested
+        .clamp(1.0, WARP_LEVELS[WARP_LEVELS.len() - 1]);
+
+    let Ok((ship, impulse, angular_impulse)) = player.get_single() else {
+        warp.factor = warp.requested;
+        return;
+    };
+
+    if impulse.0 != Vec3::ZERO || angular_impulse.0 != Vec3::ZERO {
+        if warp.requested > 1.0 {
+            info!("time warp cancelled by thrust");
+        }
+        warp.requested = 1.0;
+        warp.factor = 1.0;
+        return;
+    }
+
+    if warp.requested <= 1.0 {
+        warp.factor = 1.0;
+        return;
+    }
+
+    let horizon = warp.look_ahead * warp.requested;
+    let Some(trajectory) = predictor.predict_trajectory(ship, horizon, LOOK_AHEAD_STEPS, false)
+    else {
+        warp.factor = warp.requested;
+        return;
+    };
+
+    let frame = trajectory
+        .frame
+        .and_then(|frame| frames.get(frame).ok())
+        .copied()
+        .unwrap_or_default();
+
+    let obstacles: Vec<(Vec3, f32)> = colliders
+        .iter()
+        .filter(|(entity, ..)| rigid_body(*entity, &parents, &bodies) != ship)
+        .map(|(_, transform, collider)| {
+            let aabb = collider.aabb(&transform.affine());
+            (
+                (aabb.min + aabb.max) * 0.5,
+                (aabb.max - aabb.min).length() * 0.5,
+            )
+        })
+        .collect();
+
+    // Same rule as the soi_transition_system: the smallest sphere of influence containing the ship wins.
+    let innermost = |position: Vec3| {
+        spheres
+            .iter()
+            .filter(|(_, transform, soi)| transform.translation().distance(position) < soi.radius)
+            .min_by(|(_, _, a), (_, _, b)| a.radius.total_cmp(&b.radius))
+            .map(|(entity, ..)| entity)
+    };
+
+    let positions: Vec<Vec3> = trajectory
+        .samples
+        .iter()
+        .map(|sample| frame.transform_point(sample.position))
+        .collect();
+    let primary = positions.first().copied().and_then(innermost);
+
+    // Samples can be far apart at high warp, so obstacles are tested against the segments between them.
+    let hazard = positions
+        .windows(2)
+        .position(|segment| {
+            let (start, end) = (segment[0], segment[1]);
+            obstacles.iter().any(|(center, radius)| {
+                let along = (end - start).length_squared();
+                let t = if along > 0.0 {
+                    ((*center - start).dot(end - start) / along).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                center.distance_squared(start.lerp(end, t)) < radius * radius
+            }) || innermost(end) != primary
+        })
+        .map(|index| trajectory.time(index));
+
+    let factor = match hazard {
+        Some(time) => (time / warp.look_ahead).clamp(1.0, warp.requested),
+        None => warp.requested,
+    };
+
+    if factor != warp.factor {
+        debug!("time warp now {}x", factor);
+    }
+    warp.factor = factor;
+}